@@ -12,9 +12,6 @@ use bevy_atmosphere::prelude::*;
 use bevy_sliding_door::*;
 use menu::*;
 
-#[derive(Component)]
-pub struct SensorDoor(Entity);
-
 #[derive(Component)]
 pub struct EventEnteredText;
 
@@ -57,7 +54,7 @@ fn main() {
         CharacterPlugin,
         AtmospherePlugin,
         GearboxPlugin,
-        SlidingDoorPlugin,
+        SlidingDoorPlugin::physics(),
     ))
     .insert_gizmo_config(
         PhysicsGizmos::default(),
@@ -75,8 +72,9 @@ fn main() {
     )
     .add_observer(change_event_enter_text)
     .add_observer(change_event_exit_text)
-    .add_systems(Startup, setup_scene)
-    .add_systems(Update, (detect_enter_sensor, detect_exit_sensor));
+    .add_observer(change_request_open_text)
+    .add_observer(change_request_close_text)
+    .add_systems(Startup, setup_scene);
 
     app.run();
 }
@@ -121,9 +119,8 @@ fn setup_scene(
 
     // door
     let door_scale = Vec3::new(4.0, 4.0, 0.1);
-    let door_start_x = 0.0;
-    let door_end_x = 4.0;
-    let door_init_pos = Vec3::new(door_start_x, 2.0, -10.0);
+    let door_init_pos = Vec3::new(0.0, 2.0, -10.0);
+    let door_open_pos = door_init_pos + Vec3::new(4.0, 0.0, 0.0);
     let duration = 2.0;
     let door_entity = commands
         .spawn((
@@ -135,14 +132,16 @@ fn setup_scene(
             Mesh3d(door.clone()),
             MeshMaterial3d(door_material.clone()),
             SlidingDoor {
-                start_x: door_start_x,
-                end_x: door_end_x,
-                target_duration_secs: duration,
+                closed_position: door_init_pos,
+                open_position: door_open_pos,
+                opening_timing: DoorTiming::Duration(duration),
+                closing_timing: DoorTiming::Duration(duration),
                 waiting_secs: 3.0,
                 ..default()
             },
             Collider::cuboid(1.0, 1.0, 1.0),
-            RigidBody::Kinematic,
+            // moves as a kinematic body, so it pushes the character instead of going through it
+            PhysicsDrivenDoor,
         ))
         .id();
 
@@ -208,10 +207,8 @@ fn setup_scene(
             scale: Vec3::new(4.0, 4.0, 13.5),
             ..default()
         },
-        SensorDoor(door_entity),
+        DoorSensor::new(door_entity).with_filter(DoorSensorFilter::component::<Player>()),
         Collider::cuboid(1.0, 1.0, 1.0),
-        Sensor,
-        CollisionEventsEnabled,
     ));
 
     // UI
@@ -283,58 +280,20 @@ fn change_event_exit_text(
     }
 }
 
-fn detect_enter_sensor(
-    mut collision_event_reader: EventReader<CollisionStarted>,
-    player: Single<Entity, With<Player>>,
-    sensors: Populated<&SensorDoor>,
-    mut commands: Commands,
+fn change_request_open_text(
+    _trigger: Trigger<RequestOpen>,
     text: Single<&mut TextSpan, With<EventRequestText>>,
 ) {
     let mut textspan = text.into_inner();
 
-    let player_entity = player.into_inner();
-    for CollisionStarted(entity1, entity2) in collision_event_reader.read() {
-        let entity1 = *entity1;
-        let entity2 = *entity2;
-
-        if entity1 == player_entity {
-            if let Ok(door_sensor) = sensors.get(entity2) {
-                commands.trigger_targets(RequestOpen, door_sensor.0);
-                **textspan = "RequestOpen".into();
-            }
-        } else if entity2 == player_entity {
-            if let Ok(door_sensor) = sensors.get(entity1) {
-                commands.trigger_targets(RequestOpen, door_sensor.0);
-                **textspan = "RequestOpen".into();
-            }
-        }
-    }
+    **textspan = "RequestOpen".into();
 }
 
-fn detect_exit_sensor(
-    mut collision_event_reader: EventReader<CollisionEnded>,
-    player: Single<Entity, With<Player>>,
-    sensors: Populated<&SensorDoor>,
-    mut commands: Commands,
+fn change_request_close_text(
+    _trigger: Trigger<RequestClose>,
     text: Single<&mut TextSpan, With<EventRequestText>>,
 ) {
     let mut textspan = text.into_inner();
 
-    let player_entity = player.into_inner();
-    for CollisionEnded(entity1, entity2) in collision_event_reader.read() {
-        let entity1 = *entity1;
-        let entity2 = *entity2;
-
-        if entity1 == player_entity {
-            if let Ok(door_sensor) = sensors.get(entity2) {
-                commands.trigger_targets(RequestClose, door_sensor.0);
-                **textspan = "RequestClose".into();
-            }
-        } else if entity2 == player_entity {
-            if let Ok(door_sensor) = sensors.get(entity1) {
-                commands.trigger_targets(RequestClose, door_sensor.0);
-                **textspan = "RequestClose".into();
-            }
-        }
-    }
+    **textspan = "RequestClose".into();
 }
@@ -86,12 +86,12 @@ fn egui_menu(
             );
             if ui.button("Open all doors").clicked() {
                 for door in doors.iter() {
-                    commands.trigger_targets(RequestOpen, door);
+                    commands.trigger_targets(RequestOpen::default(), door);
                 }
             }
             if ui.button("Close all doors").clicked() {
                 for door in doors.iter() {
-                    commands.trigger_targets(RequestClose, door);
+                    commands.trigger_targets(RequestClose::default(), door);
                 }
             }
             let mut speed = time.relative_speed();
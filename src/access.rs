@@ -0,0 +1,158 @@
+use std::any::TypeId;
+
+use bevy::ecs::system::SystemId;
+use bevy::prelude::*;
+
+use crate::*;
+
+/// Who is allowed to open the door. Checked against the instigator of [`RequestOpen`] and [`RequestPartialOpen`],
+/// so a request without an instigator is only allowed by a [`DoorAccess::Custom`] check that allows it.
+/// Doors without this component can be opened by anyone, see [`check_door_access`] for when it is checked
+#[derive(Component, Clone)]
+pub enum DoorAccess {
+    /// the instigator needs to have the component with this `TypeId`, like a keycard. see [`DoorAccess::require`]
+    RequireComponent(TypeId),
+    /// the instigator needs a [`DoorFaction`] that is one of these
+    Factions(Vec<u32>),
+    /// a system registered by the user decides, with `World::register_system`
+    Custom(SystemId<In<DoorAccessCheck>, bool>),
+}
+
+impl DoorAccess {
+    /// Only lets in instigators that have the component `C`
+    pub fn require<C: Component>() -> Self {
+        Self::RequireComponent(TypeId::of::<C>())
+    }
+
+    pub fn allows(&self, world: &mut World, door: Entity, instigator: Option<Entity>) -> bool {
+        match self {
+            Self::RequireComponent(type_id) => instigator
+                .and_then(|instigator| world.get_entity(instigator).ok())
+                .is_some_and(|instigator| instigator.contains_type_id(*type_id)),
+            Self::Factions(factions) => instigator
+                .and_then(|instigator| world.get::<DoorFaction>(instigator))
+                .is_some_and(|faction| factions.contains(&faction.0)),
+            Self::Custom(system) => world
+                .run_system_with(*system, DoorAccessCheck { door, instigator })
+                .unwrap_or(false),
+        }
+    }
+}
+
+/// Faction of an entity that can open doors, checked by [`DoorAccess::Factions`]
+#[derive(Component, Clone, Copy, PartialEq, Eq)]
+pub struct DoorFaction(pub u32);
+
+/// Input of a [`DoorAccess::Custom`] check
+#[derive(Clone, Copy)]
+pub struct DoorAccessCheck {
+    pub door: Entity,
+    pub instigator: Option<Entity>,
+}
+
+/// doors that can be asked to open, see [`DoorEarlyRequests`] for doors that aren't ready yet
+type ReadyUnlockedDoor = (
+    With<SlidingDoor>,
+    Without<DoorLocked>,
+    Without<DoorEarlyRequests>,
+);
+
+/// doors that are open or already opening all the way
+pub(crate) type OpeningOrOpen = Or<(With<DoorOpening>, With<DoorOpen>, With<DoorWaiting>)>;
+
+/// triggers `granted` on the door if whoever asked is allowed to open it, otherwise denies it
+fn grant_access<G: Event>(
+    commands: &mut Commands,
+    door_entity: Entity,
+    access: Option<&DoorAccess>,
+    instigator: Option<Entity>,
+    granted: G,
+) {
+    let Some(access) = access.cloned() else {
+        commands.trigger_targets(granted, door_entity);
+        return;
+    };
+
+    // custom checks are systems, so they need the whole world to run
+    commands.queue(move |world: &mut World| {
+        if access.allows(world, door_entity, instigator) {
+            world.trigger_targets(granted, door_entity);
+        } else {
+            world.trigger_targets(
+                DoorDenied {
+                    reason: DoorDeniedReason::NoAccess,
+                    instigator,
+                },
+                door_entity,
+            );
+        }
+    });
+}
+
+/// Lets the door start opening if whoever asked is allowed to, otherwise denies it.
+/// This is checked whenever the door is not open yet, so a closing, stopped or partially open door can't be opened the rest of the way by anyone either.
+/// Only doors that are open or already opening all the way don't check again
+pub fn check_door_access(
+    trigger: Trigger<RequestOpen>,
+    mut commands: Commands,
+    doors: Query<Option<&DoorAccess>, ReadyUnlockedDoor>,
+    opening_or_open: Query<(), OpeningOrOpen>,
+) {
+    let door_entity = trigger.target();
+
+    let Ok(access) = doors.get(door_entity) else {
+        return;
+    };
+
+    if opening_or_open.contains(door_entity) {
+        return;
+    }
+
+    grant_access(
+        &mut commands,
+        door_entity,
+        access,
+        trigger.event().instigator,
+        AccessGranted,
+    );
+}
+
+/// doors whose access can be checked, with how far open they are
+type PartialAccessQuery<'w, 's> =
+    Query<'w, 's, (Option<&'static DoorAccess>, &'static DoorRuntime), ReadyUnlockedDoor>;
+
+/// Same as [`check_door_access`] for [`RequestPartialOpen`]. Only requests that would make the door more open than it is get checked,
+/// moving a door towards closed is allowed for anyone
+pub fn check_door_partial_access(
+    trigger: Trigger<RequestPartialOpen>,
+    mut commands: Commands,
+    doors: PartialAccessQuery,
+    opening_or_open: Query<(), OpeningOrOpen>,
+) {
+    let door_entity = trigger.target();
+
+    let Ok((access, runtime)) = doors.get(door_entity) else {
+        return;
+    };
+
+    // there is nowhere to move the door to, and its progress would never be a number again
+    if trigger.event().fraction.is_nan() {
+        return;
+    }
+
+    let fraction = trigger.event().fraction.clamp(0.0, 1.0);
+    let granted = PartialOpenGranted(fraction);
+
+    if opening_or_open.contains(door_entity) || fraction <= runtime.progress {
+        commands.trigger_targets(granted, door_entity);
+        return;
+    }
+
+    grant_access(
+        &mut commands,
+        door_entity,
+        access,
+        trigger.event().instigator,
+        granted,
+    );
+}
@@ -0,0 +1,246 @@
+use bevy::prelude::*;
+
+use crate::*;
+
+/// Freezes the door where it is when it gets stopped, remembering where it was heading
+pub fn freeze_stopped_door(
+    trigger: Trigger<OnAdd, DoorStopped>,
+    mut doors: Query<&mut DoorRuntime>,
+) {
+    let Ok(mut runtime) = doors.get_mut(trigger.target()) else {
+        return;
+    };
+
+    // a door stopped before it got to move at all was not heading anywhere yet
+    runtime.resume_target = runtime
+        .motion
+        .as_ref()
+        .map_or(runtime.progress, |motion| motion.to);
+    runtime.motion = None;
+    runtime.velocity = 0.0;
+}
+
+pub fn resume_door(
+    trigger: Trigger<RequestResume>,
+    mut commands: Commands,
+    doors: Query<&DoorRuntime, (With<DoorStopped>, Without<DoorEarlyRequests>)>,
+) {
+    let door_entity = trigger.target();
+
+    let Ok(runtime) = doors.get(door_entity) else {
+        return;
+    };
+    let instigator = trigger.event().instigator;
+
+    if runtime.resume_target >= 1.0 {
+        commands.trigger_targets(RequestOpen { instigator }, door_entity);
+    } else if runtime.resume_target <= 0.0 {
+        commands.trigger_targets(RequestClose { instigator }, door_entity);
+    } else {
+        commands.trigger_targets(
+            RequestPartialOpen {
+                fraction: runtime.resume_target,
+                instigator,
+            },
+            door_entity,
+        );
+    }
+}
+
+/// doors, with the state they are in
+type ToggledDoorQuery<'w, 's> = Query<
+    'w,
+    's,
+    (
+        &'static DoorRuntime,
+        Has<DoorOpening>,
+        Has<DoorClosing>,
+        Has<DoorMovingToPartial>,
+        Has<DoorStopped>,
+        Has<DoorOpen>,
+        Has<DoorPartiallyOpen>,
+        Has<DoorWaiting>,
+    ),
+    Without<DoorEarlyRequests>,
+>;
+
+pub fn toggle_door(
+    trigger: Trigger<RequestToggle>,
+    mut commands: Commands,
+    doors: ToggledDoorQuery,
+) {
+    let door_entity = trigger.target();
+
+    let Ok((runtime, opening, closing, moving_to_partial, stopped, open, partial, waiting)) =
+        doors.get(door_entity)
+    else {
+        return;
+    };
+    let instigator = trigger.event().instigator;
+
+    if opening || closing || moving_to_partial {
+        commands.trigger_targets(RequestStop, door_entity);
+    } else if stopped {
+        // go back the opposite way it was going
+        if runtime.resume_target > runtime.progress {
+            commands.trigger_targets(RequestClose { instigator }, door_entity);
+        } else {
+            commands.trigger_targets(RequestOpen { instigator }, door_entity);
+        }
+    } else if open || partial {
+        commands.trigger_targets(RequestClose { instigator }, door_entity);
+    } else if waiting {
+        // the door was already going to close, pressing again closes it without waiting
+        commands.trigger_targets(FinishedWaiting, door_entity);
+    } else {
+        commands.trigger_targets(RequestOpen { instigator }, door_entity);
+    }
+}
+
+/// Remembers that the door got a request this frame, for [`DoorMode::HoldToRun`]
+pub fn hold_door<E: Event>(
+    trigger: Trigger<E>,
+    mut doors: Query<&mut DoorRuntime, Without<DoorEarlyRequests>>,
+) {
+    if let Ok(mut runtime) = doors.get_mut(trigger.target()) {
+        runtime.held = true;
+    }
+}
+
+/// Starts a new frame for hold-to-run doors. This happens once per frame instead of once per run of the door logic,
+/// since a fixed timestep can run several times in a frame, or not at all
+pub fn release_held_doors(mut doors: Query<(&DoorMode, &mut DoorRuntime)>) {
+    for (mode, mut runtime) in doors.iter_mut() {
+        if *mode != DoorMode::HoldToRun {
+            continue;
+        }
+
+        // only the animation depends on the runtime changing
+        let runtime = runtime.bypass_change_detection();
+        runtime.held_last_frame = runtime.held;
+        runtime.held = false;
+    }
+}
+
+/// doors, with whether they are moving
+type HeldDoorQuery<'w, 's> = Query<
+    'w,
+    's,
+    (
+        Entity,
+        &'static DoorMode,
+        &'static DoorRuntime,
+        Has<DoorOpening>,
+        Has<DoorClosing>,
+    ),
+>;
+
+/// Stops hold-to-run doors that didn't get a request this frame or the last one.
+/// Requests from the last frame still count, since they can be sent after the door logic ran
+pub fn stop_released_doors(mut commands: Commands, doors: HeldDoorQuery) {
+    for (door_entity, mode, runtime, opening, closing) in doors.iter() {
+        if *mode != DoorMode::HoldToRun {
+            continue;
+        }
+
+        if (opening || closing) && !runtime.held && !runtime.held_last_frame {
+            commands.trigger_targets(RequestStop, door_entity);
+        }
+    }
+}
+
+/// Why a door refused a request
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DoorDeniedReason {
+    Locked,
+    /// the [`DoorAccess`] of the door doesn't allow whoever asked
+    NoAccess,
+}
+
+/// Triggered on a door when it refuses to open
+#[derive(Event, Clone)]
+pub struct DoorDenied {
+    pub reason: DoorDeniedReason,
+    /// who was refused, if known
+    pub instigator: Option<Entity>,
+}
+
+/// Requests that may know who made them
+pub trait InstigatedRequest {
+    fn instigator(&self) -> Option<Entity>;
+}
+
+impl InstigatedRequest for RequestOpen {
+    fn instigator(&self) -> Option<Entity> {
+        self.instigator
+    }
+}
+
+impl InstigatedRequest for RequestPartialOpen {
+    fn instigator(&self) -> Option<Entity> {
+        self.instigator
+    }
+}
+
+/// doors in any state other than closed or locked
+pub(crate) type NotClosed = Or<(
+    With<DoorOpening>,
+    With<DoorOpen>,
+    With<DoorWaiting>,
+    With<DoorClosing>,
+    With<DoorMovingToPartial>,
+    With<DoorPartiallyOpen>,
+    With<DoorStopped>,
+)>;
+
+/// Remembers a lock requested while the door isn't closed, to lock it once it has closed
+pub fn queue_door_lock(
+    trigger: Trigger<RequestLock>,
+    mut doors: Query<&mut DoorRuntime, NotClosed>,
+) {
+    if let Ok(mut runtime) = doors.get_mut(trigger.target()) {
+        runtime.lock_pending = true;
+    }
+}
+
+pub fn cancel_door_lock(trigger: Trigger<RequestUnlock>, mut doors: Query<&mut DoorRuntime>) {
+    if let Ok(mut runtime) = doors.get_mut(trigger.target()) {
+        runtime.lock_pending = false;
+    }
+}
+
+pub fn apply_pending_lock(
+    trigger: Trigger<FinishedClosing>,
+    mut commands: Commands,
+    mut doors: Query<&mut DoorRuntime>,
+) {
+    let door_entity = trigger.target();
+
+    if let Ok(mut runtime) = doors.get_mut(door_entity)
+        && runtime.lock_pending
+    {
+        runtime.lock_pending = false;
+        // runs after the door got to the Closed state
+        commands.trigger_targets(RequestLock, door_entity);
+    }
+}
+
+/// A locked door ignores requests to open, this lets whoever asked know about it.
+/// Requests are forwarded to a separate state machine, which has the state markers too, so it is skipped
+pub fn deny_locked_door<E: Event + InstigatedRequest>(
+    trigger: Trigger<E>,
+    mut commands: Commands,
+    doors: Query<(), (With<DoorLocked>, Without<DoorMachineOf>)>,
+) {
+    let door_entity = trigger.target();
+
+    if doors.contains(door_entity) {
+        commands.trigger_targets(
+            DoorDenied {
+                reason: DoorDeniedReason::Locked,
+                instigator: trigger.event().instigator(),
+            },
+            door_entity,
+        );
+    }
+}
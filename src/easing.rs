@@ -0,0 +1,206 @@
+use std::sync::Arc;
+
+use bevy::prelude::*;
+
+/// Easing used to animate a door, mapping how far along a movement is in time to how far along it is in space
+#[derive(Clone)]
+pub enum DoorEasing {
+    /// one of bevy's builtin easing functions
+    Function(EaseFunction),
+    /// any curve. its domain gets remapped to [0, 1]
+    Curve(Arc<dyn Curve<f32> + Send + Sync>),
+}
+
+impl Default for DoorEasing {
+    fn default() -> Self {
+        Self::Function(EaseFunction::QuadraticInOut)
+    }
+}
+
+impl From<EaseFunction> for DoorEasing {
+    fn from(function: EaseFunction) -> Self {
+        Self::Function(function)
+    }
+}
+
+impl DoorEasing {
+    /// Uses any curve with a bounded domain as the easing
+    ///
+    /// # Panics
+    ///
+    /// Panics if the domain of the curve is not bounded
+    pub fn custom(curve: impl Curve<f32> + Send + Sync + 'static) -> Self {
+        assert!(
+            curve.domain().is_bounded(),
+            "the curve of a door easing needs a bounded domain"
+        );
+
+        Self::Curve(Arc::new(curve))
+    }
+
+    /// Samples the easing, `t` going from 0 to 1
+    pub fn ease(&self, t: f32) -> f32 {
+        match self {
+            Self::Function(function) => function.sample_clamped(t),
+            Self::Curve(curve) => {
+                let domain = curve.domain();
+                curve.sample_clamped(domain.start() + t * domain.length())
+            }
+        }
+    }
+}
+
+/// How long a full movement of the door, from closed to open or the other way around, takes
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum DoorTiming {
+    /// the movement takes this many seconds
+    Duration(f32),
+    /// the door moves at this many units per second, so the duration depends on how far it travels.
+    /// a door with leaves takes as long as its furthest travelling leaf
+    Speed(f32),
+}
+
+impl Default for DoorTiming {
+    fn default() -> Self {
+        Self::Duration(1.0)
+    }
+}
+
+impl DoorTiming {
+    /// how long a full movement takes, for a door that travels `distance` units
+    pub fn full_duration_secs(&self, distance: f32) -> f32 {
+        match *self {
+            Self::Duration(duration_secs) => duration_secs,
+            Self::Speed(speed) if speed > 0.0 => distance / speed,
+            // a door without speed would never get anywhere, so make it instant instead
+            Self::Speed(_) => 0.0,
+        }
+    }
+}
+
+/// A movement of the door from one progress value to another
+#[derive(Clone)]
+pub struct DoorMotion {
+    /// progress the door was at when the movement started
+    pub from: f32,
+    /// progress the door will be at when the movement finishes
+    pub to: f32,
+    pub duration_secs: f32,
+    pub elapsed_secs: f32,
+    pub easing: DoorEasing,
+    /// velocity (progress per second) the door had when the movement started, which gets smoothly faded out
+    pub initial_velocity: f32,
+}
+
+impl DoorMotion {
+    pub fn new(
+        from: f32,
+        to: f32,
+        duration_secs: f32,
+        easing: DoorEasing,
+        initial_velocity: f32,
+    ) -> Self {
+        Self {
+            from,
+            to,
+            duration_secs,
+            elapsed_secs: 0.0,
+            easing,
+            initial_velocity,
+        }
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.elapsed_secs >= self.duration_secs
+    }
+
+    /// progress of the door at the current time of the movement
+    pub fn sample(&self) -> f32 {
+        // a movement without duration (for example, an instant door) is always at its end
+        let t = if self.duration_secs > 0.0 {
+            (self.elapsed_secs / self.duration_secs).clamp(0.0, 1.0)
+        } else {
+            1.0
+        };
+
+        // easings like back or elastic overshoot on purpose, so this isn't clamped
+        let eased = self.from + (self.to - self.from) * self.easing.ease(t);
+
+        // cubic hermite basis going from 0 to 0, starting with a slope of 1 and ending with a slope of 0.
+        // this carries over the velocity the door had, so a door that gets interrupted decelerates before turning back instead of jumping.
+        // it can't carry the door past being fully open or closed, unless the easing already does
+        let carried = (self.initial_velocity * self.duration_secs * (t - 2.0 * t * t + t * t * t))
+            .clamp((-eased).min(0.0), (1.0 - eased).max(0.0));
+
+        eased + carried
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EPSILON: f32 = 1e-4;
+
+    fn motion_at(motion: &DoorMotion, elapsed_secs: f32) -> f32 {
+        DoorMotion {
+            elapsed_secs,
+            ..motion.clone()
+        }
+        .sample()
+    }
+
+    #[test]
+    fn starts_at_from() {
+        let motion = DoorMotion::new(0.3, 0.8, 2.0, DoorEasing::default(), 0.4);
+
+        assert!((motion_at(&motion, 0.0) - 0.3).abs() < EPSILON);
+    }
+
+    #[test]
+    fn ends_at_to() {
+        let motion = DoorMotion::new(0.3, 0.8, 2.0, DoorEasing::default(), 0.4);
+
+        assert!((motion_at(&motion, 2.0) - 0.8).abs() < EPSILON);
+    }
+
+    #[test]
+    fn starts_with_initial_velocity() {
+        // a door that was closing gets asked to open, its easing starts with a slope of 0
+        let motion = DoorMotion::new(0.5, 1.0, 1.0, DoorEasing::default(), -0.6);
+        let delta_secs = 1e-3;
+
+        let slope = (motion_at(&motion, delta_secs) - motion_at(&motion, 0.0)) / delta_secs;
+
+        assert!((slope - -0.6).abs() < 1e-2);
+    }
+
+    #[test]
+    fn keeps_overshoot() {
+        let opening = DoorMotion::new(0.0, 1.0, 1.0, EaseFunction::BackOut.into(), 0.0);
+        let closing = DoorMotion::new(1.0, 0.0, 1.0, EaseFunction::BackOut.into(), 0.0);
+        let anticipating = DoorMotion::new(0.0, 1.0, 1.0, EaseFunction::BackIn.into(), 0.0);
+
+        assert!(motion_at(&opening, 0.7) > 1.0);
+        assert!(motion_at(&closing, 0.7) < 0.0);
+        assert!(motion_at(&anticipating, 0.3) < 0.0);
+    }
+
+    #[test]
+    fn duration_ignores_distance() {
+        assert_eq!(DoorTiming::Duration(1.5).full_duration_secs(4.0), 1.5);
+        assert_eq!(DoorTiming::Duration(1.5).full_duration_secs(0.0), 1.5);
+    }
+
+    #[test]
+    fn speed_depends_on_distance() {
+        assert_eq!(DoorTiming::Speed(2.0).full_duration_secs(4.0), 2.0);
+        assert_eq!(DoorTiming::Speed(2.0).full_duration_secs(0.0), 0.0);
+    }
+
+    #[test]
+    fn no_speed_is_instant() {
+        assert_eq!(DoorTiming::Speed(0.0).full_duration_secs(4.0), 0.0);
+        assert_eq!(DoorTiming::Speed(-1.0).full_duration_secs(4.0), 0.0);
+    }
+}
@@ -0,0 +1,59 @@
+use bevy::prelude::*;
+
+use crate::DoorEasing;
+
+/// Makes this entity a moving leaf of a door. The door entity (the one with [`SlidingDoor`](crate::SlidingDoor)) then stays still,
+/// and drives all of its leaves from how far along its animation is. Leaves can have their own [`DoorPath`](crate::DoorPath) or [`SwingDoor`](crate::SwingDoor)
+#[derive(Component)]
+#[relationship(relationship_target = DoorLeaves)]
+pub struct DoorLeafOf(pub Entity);
+
+/// All the leaves driven by this door
+#[derive(Component)]
+#[relationship_target(relationship = DoorLeafOf)]
+pub struct DoorLeaves(Vec<Entity>);
+
+/// Closed and open pose of a single door leaf
+#[derive(Component, Default, Clone)]
+#[require(Transform)]
+pub struct DoorLeaf {
+    /// local translation of the leaf when the door is fully closed
+    pub closed_position: Vec3,
+    /// local translation of the leaf when the door is fully open
+    pub open_position: Vec3,
+}
+
+/// Makes a leaf move as one panel of a telescoping door. Instead of following the whole progress of the door,
+/// the panel only moves during its own window of it, and only travels part of the way
+#[derive(Component, Clone)]
+pub struct TelescopingPanel {
+    /// fraction of the way from `closed_position` to `open_position` the panel travels when the door is fully open
+    pub travel: f32,
+    /// progress of the door at which this panel starts moving
+    pub start: f32,
+    /// progress of the door at which this panel stops moving
+    pub end: f32,
+    /// easing applied inside the window. an in-out easing avoids the panel snapping into motion when its window starts
+    pub easing: DoorEasing,
+}
+
+impl Default for TelescopingPanel {
+    fn default() -> Self {
+        Self {
+            travel: 1.0,
+            start: 0.0,
+            end: 1.0,
+            easing: EaseFunction::Linear.into(),
+        }
+    }
+}
+
+impl TelescopingPanel {
+    /// how far along this panel is, given the progress of the door
+    pub fn progress(&self, door_progress: f32) -> f32 {
+        let window = (self.end - self.start).max(f32::EPSILON);
+        let local_t = ((door_progress - self.start) / window).clamp(0.0, 1.0);
+
+        self.easing.ease(local_t) * self.travel
+    }
+}
@@ -6,83 +6,224 @@ use utils::*;
 mod plugin;
 pub use plugin::*;
 
+mod path;
+pub use path::*;
+
+mod swing;
+pub use swing::*;
+
+mod leaf;
+pub use leaf::*;
+
+mod easing;
+pub use easing::*;
+
+mod pose;
+pub use pose::*;
+
+#[cfg(feature = "avian3d")]
+mod physics;
+#[cfg(feature = "avian3d")]
+pub use physics::*;
+
+#[cfg(feature = "avian3d")]
+mod obstruction;
+#[cfg(feature = "avian3d")]
+pub use obstruction::*;
+
+#[cfg(feature = "avian3d")]
+mod sensor;
+#[cfg(feature = "avian3d")]
+pub use sensor::*;
+
 mod state_machine;
 pub use state_machine::*;
 
-#[derive(Component, Default)]
+mod control;
+pub use control::*;
+
+mod access;
+pub use access::*;
+
+mod occupancy;
+pub use occupancy::*;
+
+mod status;
+pub use status::*;
+
+/// How a door moves. Changing it while the door moves makes the door plan the rest of its movement again
+#[derive(Component, Default, Clone)]
+#[require(
+    Transform,
+    DoorRuntime,
+    DoorPreviousProgress,
+    DoorOccupancy,
+    DoorEarlyRequests,
+    DoorStatus,
+    DoorProgress
+)]
 pub struct SlidingDoor {
-    pub start_x: f32,
-    pub end_x: f32,
+    /// local translation of the door when it is fully closed
+    pub closed_position: Vec3,
+    /// local translation of the door when it is fully open
+    pub open_position: Vec3,
     pub waiting_secs: f32,
-    /// how long the opening and closing should last
-    pub target_duration_secs: f32,
+    /// how long opening should last, or how fast it should be
+    pub opening_timing: DoorTiming,
+    /// how long closing should last, or how fast it should be
+    pub closing_timing: DoorTiming,
+    /// easing used while the door opens
+    pub opening_easing: DoorEasing,
+    /// easing used while the door closes
+    pub closing_easing: DoorEasing,
+}
+
+/// Where the door is in its animation. Managed by the plugin, read it through [`DoorProgress`] and [`DoorStatus`] or its getters
+#[derive(Component, Default, Clone)]
+pub struct DoorRuntime {
+    // how far along the animation is, from 0 (closed) to 1 (open)
+    pub(crate) progress: f32,
+    // how fast the progress is changing, per second
+    pub(crate) velocity: f32,
+    // the movement currently being animated
+    pub(crate) motion: Option<DoorMotion>,
+    // the progress a partially open door should move to
+    pub(crate) partial_target: f32,
+    // the progress a stopped door was heading to
+    pub(crate) resume_target: f32,
+    // true while something is in the way of the door and it is pushing against it instead of moving
+    pub(crate) blocked: bool,
+    // the rigid body a blocked door is pushing, and the force it pushes it with
+    #[cfg(feature = "avian3d")]
+    pub(crate) push: Option<(Entity, Vec3)>,
+    // true if the door was asked to lock while it wasn't closed
+    pub(crate) lock_pending: bool,
+    // true if the door got a request this frame, for hold-to-run doors
+    pub(crate) held: bool,
+    // true if the door got a request last frame, for hold-to-run doors
+    pub(crate) held_last_frame: bool,
+}
+
+impl DoorRuntime {
+    /// how far along the animation is, from 0 (closed) to 1 (open)
+    pub fn progress(&self) -> f32 {
+        self.progress
+    }
+
+    /// how fast the progress is changing, per second
+    pub fn velocity(&self) -> f32 {
+        self.velocity
+    }
 
-    // internal data representing how far along the animation is
-    pub current_duration_secs: f32,
+    /// true while something is in the way of the door and it is pushing against it instead of moving
+    pub fn is_blocked(&self) -> bool {
+        self.blocked
+    }
+}
+
+/// Makes doors whose configuration changed plan the rest of their movement again, from where they are and as fast as they are going
+pub fn replan_changed_doors(mut doors: Query<&mut DoorRuntime, Changed<SlidingDoor>>) {
+    for mut runtime in doors.iter_mut() {
+        if runtime.motion.is_some() {
+            runtime.motion = None;
+        }
+    }
 }
 
+/// Records where the door should move to. The state machine itself only knows that it has to move there
+pub fn store_partial_target(
+    trigger: Trigger<PartialOpenGranted>,
+    mut doors: Query<&mut DoorRuntime>,
+) {
+    if let Ok(mut runtime) = doors.get_mut(trigger.target()) {
+        runtime.partial_target = trigger.event().0;
+    }
+}
+
+/// doors in one of the moving states, as needed to animate them
+type MovingDoorQuery<'w, 's, F> = Populated<
+    'w,
+    's,
+    (
+        Entity,
+        &'static SlidingDoor,
+        &'static mut DoorRuntime,
+        Option<&'static DoorLeaves>,
+        Option<&'static DoorPath>,
+        Option<&'static SwingDoor>,
+    ),
+    F,
+>;
+
 pub fn handle_door_open(
     mut commands: Commands,
-    mut opening_doors: Populated<(Entity, &mut Transform, &mut SlidingDoor), With<DoorOpening>>,
+    mut opening_doors: MovingDoorQuery<With<DoorOpening>>,
+    leaves: LeafTravelQuery,
     time: Res<Time>,
 ) {
     let delta_secs = time.delta_secs();
 
-    for (entity, mut transform, mut door) in opening_doors.iter_mut() {
-        door.current_duration_secs += delta_secs;
+    for (entity, door, mut runtime, door_leaves, path, swing) in opening_doors.iter_mut() {
+        let distance = door_travel_distance(door, door_leaves, path, swing, &leaves);
 
-        // only the X component of the door moves. if the animation has finished, this makes the final position correspond exactly to our goal
-        let mut x = door.end_x;
-
-        if door.current_duration_secs >= door.target_duration_secs {
-            // animation finished. t is not updated so that the position corresponds exactly to the target
-            // use an event to change it to the DoorOpen state
+        if advance_door(door, &mut runtime, 1.0, distance, delta_secs) {
+            // animation finished, use an event to change it to the DoorOpen state
             commands.trigger_targets(FinishedOpening, entity);
-        } else {
-            // the animation is still going
-            // this util function computes the X position for the current animation time
-            x = slide_interpolate(door.as_ref());
         }
-
-        transform.translation.x = x;
     }
 }
 
 pub fn handle_door_close(
     mut commands: Commands,
-    mut opening_doors: Populated<(Entity, &mut Transform, &mut SlidingDoor), With<DoorClosing>>,
+    mut closing_doors: MovingDoorQuery<With<DoorClosing>>,
+    leaves: LeafTravelQuery,
     time: Res<Time>,
 ) {
     let delta_secs = time.delta_secs();
 
-    for (entity, mut transform, mut door) in opening_doors.iter_mut() {
-        door.current_duration_secs -= delta_secs;
-
-        // only the X component of the door moves. if the animation has finished, this makes the final position correspond exactly to our goal
-        let mut x = door.start_x;
+    for (entity, door, mut runtime, door_leaves, path, swing) in closing_doors.iter_mut() {
+        let distance = door_travel_distance(door, door_leaves, path, swing, &leaves);
 
-        if door.current_duration_secs <= 0.0 {
-            // animation finished. t is not updated so that the position corresponds exactly to the target
-            // use an event to change it to the DoorOpen state
+        if advance_door(door, &mut runtime, 0.0, distance, delta_secs) {
+            // animation finished, use an event to change it to the Closed state
             commands.trigger_targets(FinishedClosing, entity);
-        } else {
-            // the animation is still going
-            // this util function computes the X position for the current animation time
-            x = slide_interpolate(door.as_ref());
         }
+    }
+}
 
-        transform.translation.x = x;
+pub fn handle_door_move_to_partial(
+    mut commands: Commands,
+    mut moving_doors: MovingDoorQuery<With<DoorMovingToPartial>>,
+    leaves: LeafTravelQuery,
+    time: Res<Time>,
+) {
+    let delta_secs = time.delta_secs();
+
+    for (entity, door, mut runtime, door_leaves, path, swing) in moving_doors.iter_mut() {
+        let distance = door_travel_distance(door, door_leaves, path, swing, &leaves);
+        let target = runtime.partial_target;
+
+        if advance_door(door, &mut runtime, target, distance, delta_secs) {
+            // animation finished, use an event to change it to the DoorPartiallyOpen state
+            commands.trigger_targets(FinishedMovingToPartial, entity);
+        }
     }
 }
 
 pub fn handle_door_waiting(
     mut commands: Commands,
-    mut waiting_doors: Populated<(Entity, &mut DoorWaiting, &SlidingDoor)>,
+    mut waiting_doors: Populated<(Entity, &mut DoorWaiting, &SlidingDoor, &DoorOccupancy)>,
     time: Res<Time>,
 ) {
     let delta_secs = time.delta_secs();
 
-    for (entity, mut waiting, door) in waiting_doors.iter_mut() {
+    for (entity, mut waiting, door, occupancy) in waiting_doors.iter_mut() {
+        // auto-closing doors start waiting without asking anyone, so the wait only starts once no one wants the door open
+        if occupancy.count() > 0 {
+            waiting.waiting_for_secs = 0.0;
+            continue;
+        }
+
         waiting.waiting_for_secs += delta_secs;
 
         if waiting.waiting_for_secs >= door.waiting_secs {
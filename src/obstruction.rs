@@ -0,0 +1,331 @@
+use avian3d::prelude::*;
+use bevy::prelude::*;
+
+use crate::physics::world_pose;
+use crate::utils::door_pose;
+use crate::*;
+
+/// how much further along its progress a door is looked at to know which way its leaves are heading
+const HEADING_STEP: f32 = 0.01;
+
+/// most hits looked at per leaf, since some of them (like the walls around the door) don't count as obstructions
+const MAX_HITS: u32 = 8;
+
+/// What a door does when something is in its way
+#[derive(Clone, Copy, Default)]
+pub enum ObstructionReaction {
+    /// a closing door opens again. an opening door has nowhere else to go, so it stops
+    #[default]
+    Reopen,
+    /// the door stops where it is, see [`RequestStop`]
+    Stop,
+    /// the door stays where it is while pushing the obstruction with the given force, and continues once it is out of the way.
+    /// only dynamic bodies can be pushed, anything else holds the door until it moves away by itself.
+    /// the force is applied on every physics step the door is blocked, straight to the velocity of the body
+    Push { force: f32 },
+}
+
+/// Safety edge for a door with a [`Collider`]. While the door moves, the collider of the door (or of each of its leaves)
+/// is swept ahead of it, and any rigid body in the way is an obstruction. Static bodies, like the walls the door slides into, are ignored
+#[derive(Component, Clone)]
+pub struct DoorSafetyEdge {
+    pub reaction: ObstructionReaction,
+    /// check for obstructions while the door closes
+    pub closing: bool,
+    /// check for obstructions while the door opens, like something jammed in the wall pocket
+    pub opening: bool,
+    /// how far ahead of the moving colliders obstructions are looked for
+    pub distance: f32,
+}
+
+impl Default for DoorSafetyEdge {
+    fn default() -> Self {
+        Self {
+            reaction: ObstructionReaction::default(),
+            closing: true,
+            opening: true,
+            distance: 0.05,
+        }
+    }
+}
+
+/// Triggered on a door when something is in its way
+#[derive(Event, Clone)]
+pub struct DoorObstructed {
+    /// rigid body in the way
+    pub obstruction: Entity,
+    /// true if the door was opening, false if it was closing
+    pub opening: bool,
+}
+
+type EdgeLeafQuery<'w, 's> = Query<
+    'w,
+    's,
+    (
+        &'static Transform,
+        Option<&'static ChildOf>,
+        &'static Collider,
+        &'static DoorLeaf,
+        Option<&'static TelescopingPanel>,
+        Option<&'static DoorPath>,
+        Option<&'static SwingDoor>,
+    ),
+    Without<SlidingDoor>,
+>;
+
+/// moving doors that check for obstructions
+type EdgeDoorQuery<'w, 's> = Query<
+    'w,
+    's,
+    (
+        Entity,
+        &'static SlidingDoor,
+        &'static mut DoorRuntime,
+        &'static DoorSafetyEdge,
+        &'static Transform,
+        Option<&'static ChildOf>,
+        Option<&'static Collider>,
+        Option<&'static DoorLeaves>,
+        Option<&'static DoorPath>,
+        Option<&'static SwingDoor>,
+    ),
+    Or<(
+        With<DoorOpening>,
+        With<DoorClosing>,
+        With<DoorMovingToPartial>,
+    )>,
+>;
+
+/// where a door or leaf currently is in world space, and which way it is heading
+fn sweep(
+    child_of: Option<&ChildOf>,
+    parents: &Query<&GlobalTransform>,
+    pose_at: impl Fn(f32) -> Transform,
+    t: f32,
+    next_t: f32,
+) -> Option<(Transform, Dir3)> {
+    let pose = world_pose(child_of, parents, pose_at(t));
+    let next_pose = world_pose(child_of, parents, pose_at(next_t));
+
+    // parts that are not moving right now can't run into anything
+    Dir3::new(next_pose.translation - pose.translation)
+        .ok()
+        .map(|direction| (pose, direction))
+}
+
+/// Sweeps the colliders of moving doors ahead of them, and reacts to whatever is in the way
+#[allow(clippy::too_many_arguments)]
+pub fn detect_door_obstructions(
+    mut commands: Commands,
+    mut doors: EdgeDoorQuery,
+    states: Query<(Has<DoorOpening>, Has<DoorClosing>)>,
+    leaves: EdgeLeafQuery,
+    parents: Query<&GlobalTransform>,
+    colliders: Query<(Option<&ColliderOf>, Has<Sensor>)>,
+    rigid_bodies: Query<&RigidBody>,
+    spatial_query: SpatialQuery,
+) {
+    for (
+        door_entity,
+        door,
+        mut runtime,
+        edge,
+        transform,
+        child_of,
+        collider,
+        door_leaves,
+        path,
+        swing,
+    ) in doors.iter_mut()
+    {
+        let Ok((opening, closing)) = states.get(door_entity) else {
+            continue;
+        };
+        let target = if opening {
+            1.0
+        } else if closing {
+            0.0
+        } else {
+            runtime.partial_target
+        };
+        let is_opening = target > runtime.progress;
+
+        let checked = if is_opening {
+            edge.opening
+        } else {
+            edge.closing
+        };
+
+        if !checked {
+            if runtime.blocked {
+                runtime.blocked = false;
+                runtime.push = None;
+            }
+            continue;
+        }
+
+        let next_progress =
+            runtime.progress + (target - runtime.progress).clamp(-HEADING_STEP, HEADING_STEP);
+
+        let sweeps: Vec<(&Collider, Transform, Dir3)> = match door_leaves {
+            Some(door_leaves) => door_leaves
+                .iter()
+                .filter_map(|leaf_entity| leaves.get(leaf_entity).ok())
+                .filter_map(
+                    |(
+                        leaf_transform,
+                        leaf_child_of,
+                        leaf_collider,
+                        leaf,
+                        panel,
+                        leaf_path,
+                        leaf_swing,
+                    )| {
+                        let leaf_t = |t| panel.map_or(t, |panel| panel.progress(t));
+
+                        sweep(
+                            leaf_child_of,
+                            &parents,
+                            |t| {
+                                door_pose(
+                                    leaf_transform,
+                                    leaf.closed_position,
+                                    leaf.open_position,
+                                    leaf_path,
+                                    leaf_swing,
+                                    t,
+                                )
+                            },
+                            leaf_t(runtime.progress),
+                            leaf_t(next_progress),
+                        )
+                        .map(|(pose, direction)| (leaf_collider, pose, direction))
+                    },
+                )
+                .collect(),
+            None => collider
+                .and_then(|collider| {
+                    sweep(
+                        child_of,
+                        &parents,
+                        |t| {
+                            door_pose(
+                                transform,
+                                door.closed_position,
+                                door.open_position,
+                                path,
+                                swing,
+                                t,
+                            )
+                        },
+                        runtime.progress,
+                        next_progress,
+                    )
+                    .map(|(pose, direction)| (collider, pose, direction))
+                })
+                .into_iter()
+                .collect(),
+        };
+
+        // the door can't get in its own way
+        let is_door = |body: Entity| {
+            body == door_entity
+                || door_leaves
+                    .is_some_and(|door_leaves| door_leaves.iter().any(|leaf| leaf == body))
+        };
+
+        let obstruction = sweeps.into_iter().find_map(|(collider, pose, direction)| {
+            spatial_query
+                .shape_hits(
+                    collider,
+                    pose.translation,
+                    pose.rotation,
+                    direction,
+                    MAX_HITS,
+                    &ShapeCastConfig::from_max_distance(edge.distance),
+                    &SpatialQueryFilter::default(),
+                )
+                .into_iter()
+                .filter_map(|hit| {
+                    let (collider_of, sensor) = colliders.get(hit.entity).ok()?;
+                    let body = collider_of.map_or(hit.entity, |collider_of| collider_of.body);
+
+                    // colliders without a rigid body are static too
+                    let moving_body = rigid_bodies
+                        .get(body)
+                        .is_ok_and(|rigid_body| !rigid_body.is_static());
+
+                    (!sensor && moving_body && !is_door(body)).then_some((hit.distance, body))
+                })
+                .min_by(|(a, _), (b, _)| a.total_cmp(b))
+                .map(|(_, body)| (body, direction))
+        });
+
+        let push = match (obstruction, edge.reaction) {
+            (Some((obstruction, direction)), ObstructionReaction::Push { force }) => {
+                Some((obstruction, direction * force))
+            }
+            _ => None,
+        };
+        if runtime.push != push {
+            runtime.blocked = push.is_some();
+            runtime.push = push;
+        }
+
+        let Some((obstruction, _)) = obstruction else {
+            continue;
+        };
+
+        commands.trigger_targets(
+            DoorObstructed {
+                obstruction,
+                opening: is_opening,
+            },
+            door_entity,
+        );
+
+        match edge.reaction {
+            ObstructionReaction::Reopen if !is_opening => {
+                // reopening for safety doesn't depend on who is allowed through
+                commands.trigger_targets(AccessGranted, door_entity);
+            }
+            ObstructionReaction::Reopen | ObstructionReaction::Stop => {
+                commands.trigger_targets(RequestStop, door_entity);
+            }
+            // pushed by push_door_obstructions on each physics step
+            ObstructionReaction::Push { .. } => {}
+        }
+    }
+}
+
+/// Pushes whatever blocks a door, once per physics step so the push doesn't depend on how often the door logic runs
+pub fn push_door_obstructions(
+    doors: Query<&DoorRuntime>,
+    mut bodies: Query<(&RigidBody, &ComputedMass, &mut LinearVelocity)>,
+    time: Res<Time>,
+) {
+    let delta_secs = time.delta_secs();
+
+    for (obstruction, force) in doors.iter().filter_map(|runtime| runtime.push) {
+        let Ok((rigid_body, mass, mut linear_velocity)) = bodies.get_mut(obstruction) else {
+            continue;
+        };
+
+        if rigid_body.is_dynamic() {
+            linear_velocity.0 += force * mass.inverse() * delta_secs;
+        }
+    }
+}
+
+/// A door that stops moving, or stops checking for obstructions, isn't blocked anymore
+pub fn release_door_obstruction<C: Component>(
+    trigger: Trigger<OnRemove, C>,
+    mut doors: Query<&mut DoorRuntime>,
+) {
+    if let Ok(mut runtime) = doors.get_mut(trigger.target())
+        && runtime.blocked
+    {
+        runtime.blocked = false;
+        runtime.push = None;
+    }
+}
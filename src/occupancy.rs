@@ -0,0 +1,89 @@
+use bevy::ecs::entity::EntityHashSet;
+use bevy::prelude::*;
+
+use crate::*;
+
+/// Everyone that asked the door to open and hasn't asked it to close yet.
+/// The door only closes once this is empty. Each instigator counts once, no matter how many times it asked
+#[derive(Component, Default, Clone)]
+pub struct DoorOccupancy(pub EntityHashSet);
+
+impl DoorOccupancy {
+    pub fn count(&self) -> usize {
+        self.0.len()
+    }
+}
+
+pub fn track_open_request(
+    trigger: Trigger<RequestOpen>,
+    mut doors: Query<&mut DoorOccupancy, Without<DoorEarlyRequests>>,
+) {
+    if let Ok(mut occupancy) = doors.get_mut(trigger.target())
+        && let Some(instigator) = trigger.event().instigator
+    {
+        occupancy.0.insert(instigator);
+    }
+}
+
+/// whoever was denied isn't keeping the door open
+pub fn forget_denied_instigator(
+    trigger: Trigger<DoorDenied>,
+    mut doors: Query<&mut DoorOccupancy>,
+) {
+    if let Ok(mut occupancy) = doors.get_mut(trigger.target())
+        && let Some(instigator) = trigger.event().instigator
+    {
+        occupancy.0.remove(&instigator);
+    }
+}
+
+/// Lets the door close once no one wants it open anymore.
+/// A request without an instigator always closes the door
+pub fn check_door_occupancy(
+    trigger: Trigger<RequestClose>,
+    mut commands: Commands,
+    mut doors: Query<&mut DoorOccupancy, Without<DoorEarlyRequests>>,
+    entities: Query<()>,
+) {
+    let door_entity = trigger.target();
+
+    let Ok(mut occupancy) = doors.get_mut(door_entity) else {
+        return;
+    };
+
+    match trigger.event().instigator {
+        Some(instigator) => {
+            occupancy.0.remove(&instigator);
+        }
+        None => occupancy.0.clear(),
+    }
+    // despawned entities can't ask for the door to close anymore
+    occupancy
+        .0
+        .retain(|&instigator| entities.contains(instigator));
+
+    if occupancy.0.is_empty() {
+        commands.trigger_targets(CloseGranted, door_entity);
+    }
+}
+
+/// Lets the door close if everyone keeping it open got despawned
+pub fn prune_door_occupancy(
+    mut commands: Commands,
+    mut doors: Query<(Entity, &mut DoorOccupancy)>,
+    entities: Query<()>,
+) {
+    for (door_entity, mut occupancy) in doors.iter_mut() {
+        if occupancy.0.is_empty() {
+            continue;
+        }
+
+        occupancy
+            .0
+            .retain(|&instigator| entities.contains(instigator));
+
+        if occupancy.0.is_empty() {
+            commands.trigger_targets(CloseGranted, door_entity);
+        }
+    }
+}
@@ -0,0 +1,161 @@
+use std::sync::Arc;
+
+use bevy::ecs::entity::EntityHashSet;
+use bevy::prelude::*;
+
+use crate::{DoorLeaf, SlidingDoor};
+
+/// Makes the door follow a curve instead of sliding in a straight line from `closed_position` to `open_position`.
+/// The start of the curve's domain is the closed position, and the end is the open position
+#[derive(Component, Clone)]
+pub struct DoorPath {
+    curve: Arc<dyn Curve<Vec3> + Send + Sync>,
+    length: f32,
+}
+
+/// number of straight segments used to measure the length of a path
+const LENGTH_SEGMENTS: usize = 32;
+
+impl DoorPath {
+    /// Uses any curve with a bounded domain as the path
+    ///
+    /// # Panics
+    ///
+    /// Panics if the domain of the curve is not bounded
+    pub fn new(curve: impl Curve<Vec3> + Send + Sync + 'static) -> Self {
+        assert!(
+            curve.domain().is_bounded(),
+            "the curve of a door path needs a bounded domain"
+        );
+
+        let domain = curve.domain();
+        let mut previous = curve.sample_clamped(domain.start());
+        let mut length = 0.0;
+
+        for segment in 1..=LENGTH_SEGMENTS {
+            let point = curve.sample_clamped(
+                domain.start() + domain.length() * segment as f32 / LENGTH_SEGMENTS as f32,
+            );
+            length += previous.distance(point);
+            previous = point;
+        }
+
+        Self {
+            curve: Arc::new(curve),
+            length,
+        }
+    }
+
+    /// Cubic bézier from `closed` to `open`, bent towards the two control points
+    pub fn bezier(closed: Vec3, control_a: Vec3, control_b: Vec3, open: Vec3) -> Self {
+        let curve = CubicBezier::new([[closed, control_a, control_b, open]])
+            .to_curve()
+            .expect("a single bézier segment is always valid");
+
+        Self::new(curve)
+    }
+
+    /// Catmull-Rom spline that starts at `closed`, goes through every waypoint in order, and ends at `open`
+    pub fn catmull_rom(
+        closed: Vec3,
+        waypoints: impl IntoIterator<Item = Vec3>,
+        open: Vec3,
+    ) -> Self {
+        let points = std::iter::once(closed)
+            .chain(waypoints)
+            .chain(std::iter::once(open));
+
+        let curve = CubicCardinalSpline::new_catmull_rom(points)
+            .to_curve()
+            .expect("there are always at least 2 control points");
+
+        Self::new(curve)
+    }
+
+    /// Approximate length of the path
+    pub fn length(&self) -> f32 {
+        self.length
+    }
+
+    /// Samples the path, `t` going from 0 (closed) to 1 (open)
+    pub fn sample(&self, t: f32) -> Vec3 {
+        let domain = self.curve.domain();
+
+        self.curve
+            .sample_clamped(domain.start() + t * domain.length())
+    }
+}
+
+/// Builds the door's [`DoorPath`] as a Catmull-Rom spline going through `closed_position`, the door's [`DoorWaypoint`] children, and `open_position`.
+/// Works on both doors and [`DoorLeaf`]s
+#[derive(Component, Default)]
+pub struct DoorWaypointPath;
+
+/// Marks a child of a door as a point its path goes through. Waypoints are visited in ascending order of the index.
+///
+/// The translation of the waypoint is read as is, in the same space as the door's positions, and not relative to the door
+#[derive(Component)]
+pub struct DoorWaypoint(pub u32);
+
+/// doors and leaves following their waypoints, with the positions the path goes between
+type WaypointPathQuery<'w, 's> = Query<
+    'w,
+    's,
+    (
+        Option<&'static SlidingDoor>,
+        Option<&'static DoorLeaf>,
+        Option<&'static Children>,
+    ),
+    With<DoorWaypointPath>,
+>;
+
+/// doors and leaves that just started following their waypoints, or got waypoints added or removed
+type ChangedWaypointPaths = (
+    With<DoorWaypointPath>,
+    Or<(Added<DoorWaypointPath>, Changed<Children>)>,
+);
+
+/// waypoints that were added or moved
+type ChangedWaypoints = (
+    With<DoorWaypoint>,
+    Or<(Changed<DoorWaypoint>, Changed<Transform>)>,
+);
+
+/// Rebuilds the path of doors with [`DoorWaypointPath`] whenever their waypoints change
+pub fn build_waypoint_paths(
+    mut commands: Commands,
+    doors: WaypointPathQuery,
+    changed_doors: Query<Entity, ChangedWaypointPaths>,
+    changed_waypoints: Query<&ChildOf, ChangedWaypoints>,
+    waypoints: Query<(&DoorWaypoint, &Transform)>,
+) {
+    let dirty_doors: EntityHashSet = changed_doors
+        .iter()
+        .chain(changed_waypoints.iter().map(ChildOf::parent))
+        .collect();
+
+    for door_entity in dirty_doors {
+        // leaves have their own positions, which take priority
+        let (closed, open, children) = match doors.get(door_entity) {
+            Ok((_, Some(leaf), children)) => (leaf.closed_position, leaf.open_position, children),
+            Ok((Some(door), None, children)) => {
+                (door.closed_position, door.open_position, children)
+            }
+            _ => continue,
+        };
+
+        let mut points: Vec<(u32, Vec3)> = children
+            .into_iter()
+            .flatten()
+            .filter_map(|child| waypoints.get(*child).ok())
+            .map(|(waypoint, transform)| (waypoint.0, transform.translation))
+            .collect();
+        points.sort_by_key(|(index, _)| *index);
+
+        commands.entity(door_entity).insert(DoorPath::catmull_rom(
+            closed,
+            points.into_iter().map(|(_, point)| point),
+            open,
+        ));
+    }
+}
@@ -0,0 +1,69 @@
+use avian3d::prelude::*;
+use bevy::prelude::*;
+
+use crate::DoorPoseTarget;
+
+/// Moves the door (or leaf) through avian's physics by setting its velocity towards its pose, instead of teleporting its `Transform`.
+/// This way the physics engine knows how it moves, and bodies next to it get pushed and carried correctly.
+///
+/// The door logic has to run exactly once before every physics step for this, with [`SlidingDoorPlugin::physics`](crate::SlidingDoorPlugin::physics)
+/// (or [`SlidingDoorPlugin::new`](crate::SlidingDoorPlugin::new) with the schedule avian runs in). Otherwise physics steps without a new pose
+/// stop the door, and the ones after several poses jump to catch up
+#[derive(Component, Default)]
+#[require(RigidBody::Kinematic, DoorPoseTarget)]
+pub struct PhysicsDrivenDoor;
+
+/// doors and leaves moved through physics, with where they are and how fast they move
+type DrivenBodyQuery<'w, 's> = Query<
+    'w,
+    's,
+    (
+        &'static DoorPoseTarget,
+        Option<&'static ChildOf>,
+        &'static Position,
+        &'static Rotation,
+        &'static mut LinearVelocity,
+        &'static mut AngularVelocity,
+    ),
+    With<PhysicsDrivenDoor>,
+>;
+
+/// Runs at the start of each physics step, so that the door arrives at the pose the door logic gave it by the end of it
+pub fn drive_physics_doors(
+    mut bodies: DrivenBodyQuery,
+    parents: Query<&GlobalTransform>,
+    time: Res<Time>,
+) {
+    let delta_secs = time.delta_secs();
+
+    if delta_secs <= 0.0 {
+        return;
+    }
+
+    for (pose_target, child_of, position, rotation, mut linear_velocity, mut angular_velocity) in
+        bodies.iter_mut()
+    {
+        let target = world_pose(child_of, &parents, pose_target.0);
+
+        linear_velocity.0 = (target.translation - position.0) / delta_secs;
+
+        let mut rotation_delta = target.rotation * rotation.0.inverse();
+        // take the shortest way around
+        if rotation_delta.w < 0.0 {
+            rotation_delta = -rotation_delta;
+        }
+        angular_velocity.0 = rotation_delta.to_scaled_axis() / delta_secs;
+    }
+}
+
+/// the pose is relative to the parent, but physics happens in world space
+pub(crate) fn world_pose(
+    child_of: Option<&ChildOf>,
+    parents: &Query<&GlobalTransform>,
+    pose: Transform,
+) -> Transform {
+    match child_of.and_then(|child_of| parents.get(child_of.parent()).ok()) {
+        Some(parent_transform) => parent_transform.mul_transform(pose).compute_transform(),
+        None => pose,
+    }
+}
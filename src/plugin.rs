@@ -1,26 +1,268 @@
+use bevy::ecs::{intern::Interned, schedule::ScheduleLabel};
+use bevy::transform::TransformSystem;
 use bevy_gearbox::prelude::{replay_deferred_event, transition_listener, StateComponentAppExt};
 
 use super::*;
 
-pub struct SlidingDoorPlugin;
+/// Sets the systems of the plugin run in, in this order, so that other systems can be ordered around them
+#[derive(SystemSet, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SlidingDoorSystems {
+    /// processing of requests made to the doors
+    Requests,
+    /// timers, like how long an open door waits before closing
+    Timers,
+    /// advancing the animation of moving doors and moving them into place
+    Animation,
+    /// reacting to the doors after they have moved
+    Events,
+}
+
+pub struct SlidingDoorPlugin {
+    /// schedule the door logic and animations run in
+    pub schedule: Interned<dyn ScheduleLabel>,
+    /// if true, the pose of the doors is interpolated in `PostUpdate` between runs of the door logic.
+    /// meant for when `schedule` is a fixed timestep schedule, like `FixedUpdate`.
+    /// the doors are still moved to their actual pose in `schedule`, so that physics sees where they really are
+    pub interpolate: bool,
+}
+
+impl Default for SlidingDoorPlugin {
+    fn default() -> Self {
+        Self {
+            schedule: Update.intern(),
+            interpolate: false,
+        }
+    }
+}
+
+impl SlidingDoorPlugin {
+    /// Runs the door logic in the given schedule
+    pub fn new(schedule: impl ScheduleLabel) -> Self {
+        Self {
+            schedule: schedule.intern(),
+            ..default()
+        }
+    }
+
+    /// Runs the door logic in `FixedUpdate`, interpolating the pose of the doors in between
+    pub fn fixed() -> Self {
+        Self {
+            schedule: FixedUpdate.intern(),
+            interpolate: true,
+        }
+    }
+
+    /// Runs the door logic in `FixedPostUpdate` right before avian's physics step, interpolating the pose of the doors in between.
+    /// This way doors have moved before avian reads their transforms on every physics step.
+    /// Meant for avian running in its default schedule, for another one use [`SlidingDoorPlugin::new`] with that schedule
+    #[cfg(feature = "avian3d")]
+    pub fn physics() -> Self {
+        Self {
+            schedule: FixedPostUpdate.intern(),
+            interpolate: true,
+        }
+    }
+}
 
 impl Plugin for SlidingDoorPlugin {
     fn build(&self, app: &mut App) {
         app.add_observer(create_door_state_machine)
+            .add_observer(remove_door_state_machine)
             .add_observer(transition_listener::<RequestOpen>)
-            .add_observer(transition_listener::<RequestClose>)
+            .add_observer(transition_listener::<CloseGranted>)
+            .add_observer(transition_listener::<AccessGranted>)
             .add_observer(transition_listener::<FinishedOpening>)
             .add_observer(transition_listener::<FinishedClosing>)
             .add_observer(transition_listener::<FinishedWaiting>)
-            .add_observer(replay_deferred_event::<RequestClose>)
+            .add_observer(transition_listener::<PartialOpenGranted>)
+            .add_observer(transition_listener::<FinishedMovingToPartial>)
+            .add_observer(transition_listener::<RequestStop>)
+            .add_observer(transition_listener::<RequestLock>)
+            .add_observer(transition_listener::<RequestUnlock>)
+            .add_observer(replay_deferred_event::<CloseGranted>)
+            .add_observer(forward_to_door_machine::<RequestOpen>)
+            .add_observer(forward_to_door_machine::<CloseGranted>)
+            .add_observer(forward_to_door_machine::<AccessGranted>)
+            .add_observer(forward_to_door_machine::<FinishedOpening>)
+            .add_observer(forward_to_door_machine::<FinishedClosing>)
+            .add_observer(forward_to_door_machine::<FinishedWaiting>)
+            .add_observer(forward_to_door_machine::<PartialOpenGranted>)
+            .add_observer(forward_to_door_machine::<FinishedMovingToPartial>)
+            .add_observer(forward_to_door_machine::<RequestStop>)
+            .add_observer(forward_to_door_machine::<RequestLock>)
+            .add_observer(forward_to_door_machine::<RequestUnlock>)
+            .add_observer(pick_swing_side)
+            .add_observer(store_partial_target)
+            .add_observer(freeze_stopped_door)
+            .add_observer(resume_door)
+            .add_observer(toggle_door)
+            .add_observer(queue_door_lock)
+            .add_observer(cancel_door_lock)
+            .add_observer(apply_pending_lock)
+            .add_observer(deny_locked_door::<RequestOpen>)
+            .add_observer(deny_locked_door::<RequestPartialOpen>)
+            .add_observer(check_door_access)
+            .add_observer(check_door_partial_access)
+            .add_observer(track_open_request)
+            .add_observer(forget_denied_instigator)
+            .add_observer(check_door_occupancy)
+            .add_observer(hold_door::<RequestOpen>)
+            .add_observer(hold_door::<RequestClose>)
+            .add_observer(buffer_early_request::<RequestOpen>)
+            .add_observer(buffer_early_request::<RequestClose>)
+            .add_observer(buffer_early_request::<RequestPartialOpen>)
+            .add_observer(buffer_early_request::<RequestStop>)
+            .add_observer(buffer_early_request::<RequestLock>)
+            .add_observer(buffer_early_request::<RequestUnlock>)
+            .add_observer(buffer_early_request::<RequestOpenAwayFrom>)
+            .add_observer(buffer_early_request::<RequestToggle>)
+            .add_observer(buffer_early_request::<RequestResume>)
+            .add_observer(mirror_state_component::<DoorClosed>)
+            .add_observer(unmirror_state_component::<DoorClosed>)
+            .add_observer(mirror_state_component::<DoorOpen>)
+            .add_observer(unmirror_state_component::<DoorOpen>)
+            .add_observer(mirror_state_component::<DoorClosing>)
+            .add_observer(unmirror_state_component::<DoorClosing>)
+            .add_observer(mirror_state_component::<DoorOpening>)
+            .add_observer(unmirror_state_component::<DoorOpening>)
+            .add_observer(mirror_state_component::<DoorWaiting>)
+            .add_observer(unmirror_state_component::<DoorWaiting>)
+            .add_observer(mirror_state_component::<DoorMovingToPartial>)
+            .add_observer(unmirror_state_component::<DoorMovingToPartial>)
+            .add_observer(mirror_state_component::<DoorPartiallyOpen>)
+            .add_observer(unmirror_state_component::<DoorPartiallyOpen>)
+            .add_observer(mirror_state_component::<DoorStopped>)
+            .add_observer(unmirror_state_component::<DoorStopped>)
+            .add_observer(mirror_state_component::<DoorLocked>)
+            .add_observer(unmirror_state_component::<DoorLocked>)
+            .add_observer(enter_door_status::<DoorClosed>)
+            .add_observer(enter_door_status::<DoorOpen>)
+            .add_observer(enter_door_status::<DoorClosing>)
+            .add_observer(enter_door_status::<DoorOpening>)
+            .add_observer(enter_door_status::<DoorWaiting>)
+            .add_observer(enter_door_status::<DoorMovingToPartial>)
+            .add_observer(enter_door_status::<DoorPartiallyOpen>)
+            .add_observer(enter_door_status::<DoorStopped>)
+            .add_observer(enter_door_status::<DoorLocked>)
+            .add_observer(door_opened)
+            .add_observer(door_closed)
+            .add_event::<DoorStartedOpening>()
+            .add_event::<DoorOpened>()
+            .add_event::<DoorStartedClosing>()
+            .add_event::<DoorFinishedClosing>()
+            .add_event::<DoorWaitStarted>()
+            .add_event::<DoorReversed>()
             .add_state_component::<DoorClosed>()
             .add_state_component::<DoorOpen>()
             .add_state_component::<DoorClosing>()
             .add_state_component::<DoorOpening>()
             .add_state_component::<DoorWaiting>()
+            .add_state_component::<DoorMovingToPartial>()
+            .add_state_component::<DoorPartiallyOpen>()
+            .add_state_component::<DoorStopped>()
+            .add_state_component::<DoorLocked>();
+
+        #[cfg(feature = "avian3d")]
+        app.add_observer(release_door_obstruction::<DoorOpening>)
+            .add_observer(release_door_obstruction::<DoorClosing>)
+            .add_observer(release_door_obstruction::<DoorMovingToPartial>)
+            .add_observer(release_door_obstruction::<DoorSafetyEdge>);
+
+        app.configure_sets(
+            self.schedule,
+            (
+                SlidingDoorSystems::Requests,
+                SlidingDoorSystems::Timers,
+                SlidingDoorSystems::Animation,
+                SlidingDoorSystems::Events,
+            )
+                .chain(),
+        )
+        .add_systems(
+            self.schedule,
+            (
+                prune_door_occupancy.in_set(SlidingDoorSystems::Requests),
+                (handle_door_waiting, stop_released_doors).in_set(SlidingDoorSystems::Timers),
+                update_door_progress.in_set(SlidingDoorSystems::Events),
+            ),
+        );
+
+        app.add_systems(First, release_held_doors);
+
+        if self.interpolate {
+            app.add_systems(
+                self.schedule,
+                (
+                    (
+                        build_waypoint_paths,
+                        store_previous_progress,
+                        replan_changed_doors,
+                    ),
+                    (
+                        handle_door_open,
+                        handle_door_close,
+                        handle_door_move_to_partial,
+                    ),
+                    update_door_poses,
+                )
+                    .chain()
+                    .in_set(SlidingDoorSystems::Animation),
+            )
             .add_systems(
-                Update,
-                (handle_door_open, handle_door_close, handle_door_waiting),
+                PostUpdate,
+                interpolate_door_poses.in_set(SlidingDoorSystems::Animation),
+            );
+        } else {
+            app.add_systems(
+                self.schedule,
+                (
+                    (build_waypoint_paths, replan_changed_doors),
+                    (
+                        handle_door_open,
+                        handle_door_close,
+                        handle_door_move_to_partial,
+                    ),
+                    update_door_poses,
+                )
+                    .chain()
+                    .in_set(SlidingDoorSystems::Animation),
             );
+        }
+
+        // doors need to be in their final pose before anything reads their transforms.
+        // schedules before PostUpdate are already done by then, so only the interpolation (or door logic running in PostUpdate) needs it
+        app.configure_sets(
+            PostUpdate,
+            SlidingDoorSystems::Animation.before(TransformSystem::TransformPropagate),
+        );
+
+        // only applies when the door logic runs in the same schedule as avian, see SlidingDoorPlugin::physics.
+        // in a schedule like Update, the doors move after physics already ran for the frame
+        #[cfg(feature = "avian3d")]
+        app.configure_sets(
+            self.schedule,
+            SlidingDoorSystems::Animation.before(avian3d::prelude::PhysicsSet::Prepare),
+        );
+
+        // sensors and obstructions turn into requests before the doors move
+        #[cfg(feature = "avian3d")]
+        app.add_systems(
+            self.schedule,
+            (update_door_sensors, detect_door_obstructions).in_set(SlidingDoorSystems::Requests),
+        );
+
+        #[cfg(feature = "avian3d")]
+        app.add_systems(
+            avian3d::prelude::PhysicsSchedule,
+            drive_physics_doors
+                .in_set(avian3d::prelude::PhysicsStepSet::First)
+                .in_set(SlidingDoorSystems::Animation),
+        );
+
+        #[cfg(feature = "avian3d")]
+        app.add_systems(
+            avian3d::prelude::PhysicsSchedule,
+            push_door_obstructions.in_set(avian3d::prelude::PhysicsStepSet::First),
+        );
     }
 }
@@ -0,0 +1,221 @@
+use bevy::ecs::{component::HookContext, world::DeferredWorld};
+use bevy::prelude::*;
+
+use crate::utils::{door_pose, write_pose};
+use crate::{
+    DoorLeaf, DoorLeaves, DoorPath, DoorRuntime, SlidingDoor, SwingDoor, TelescopingPanel,
+};
+
+/// When present, the pose of the door or leaf is written here instead of into its `Transform`,
+/// so that something else (like the physics engine) can be responsible for moving it there
+#[derive(Component, Default, Clone, Copy)]
+#[component(on_add = start_at_current_transform)]
+pub struct DoorPoseTarget(pub Transform);
+
+fn start_at_current_transform(mut world: DeferredWorld, context: HookContext) {
+    // until the door first moves, its target is wherever it already is
+    if let Some(transform) = world.get::<Transform>(context.entity).copied()
+        && let Some(mut pose_target) = world.get_mut::<DoorPoseTarget>(context.entity)
+    {
+        pose_target.0 = transform;
+    }
+}
+
+/// Progress of the door before the last time the door logic ran, used to interpolate its pose between fixed timesteps
+#[derive(Component, Default, Clone, Copy)]
+pub struct DoorPreviousProgress(pub f32);
+
+type LeafPoseQuery<'w, 's> = Query<
+    'w,
+    's,
+    (
+        &'static mut Transform,
+        Option<&'static mut DoorPoseTarget>,
+        &'static DoorLeaf,
+        Option<&'static TelescopingPanel>,
+        Option<&'static DoorPath>,
+        Option<&'static SwingDoor>,
+    ),
+    Without<SlidingDoor>,
+>;
+
+/// doors, as needed to move them to the pose matching their progress
+type DoorPoseData = (
+    &'static mut Transform,
+    Option<&'static mut DoorPoseTarget>,
+    &'static SlidingDoor,
+    &'static DoorRuntime,
+    Option<&'static DoorLeaves>,
+    Option<&'static DoorPath>,
+    Option<&'static SwingDoor>,
+);
+
+/// doors, with what is needed to know if their pose has to be interpolated
+type InterpolatedDoorQuery<'w, 's> = Query<
+    'w,
+    's,
+    (
+        &'static mut Transform,
+        Has<DoorPoseTarget>,
+        Ref<'static, SlidingDoor>,
+        Ref<'static, DoorRuntime>,
+        Ref<'static, DoorPreviousProgress>,
+        Option<&'static DoorLeaves>,
+        Option<&'static DoorPath>,
+        Option<&'static SwingDoor>,
+    ),
+>;
+
+/// every leaf follows the progress of the door they belong to.
+/// leaves with a [`DoorPoseTarget`] can be left to whatever moves them instead
+fn pose_leaves(
+    door_leaves: &DoorLeaves,
+    leaves: &mut LeafPoseQuery,
+    t: f32,
+    skip_pose_targets: bool,
+) {
+    for leaf_entity in door_leaves.iter() {
+        if let Ok((mut leaf_transform, mut leaf_pose_target, leaf, panel, leaf_path, leaf_swing)) =
+            leaves.get_mut(leaf_entity)
+        {
+            if skip_pose_targets && leaf_pose_target.is_some() {
+                continue;
+            }
+
+            // telescoping panels move during their own part of the animation
+            let leaf_t = panel.map_or(t, |panel| panel.progress(t));
+
+            let pose = door_pose(
+                &leaf_transform,
+                leaf.closed_position,
+                leaf.open_position,
+                leaf_path,
+                leaf_swing,
+                leaf_t,
+            );
+            write_pose(&mut leaf_transform, leaf_pose_target.as_deref_mut(), pose);
+        }
+    }
+}
+
+/// Moves doors whose animation changed to the matching pose.
+/// A door with leaves stays still and moves each leaf instead
+pub fn update_door_poses(
+    mut doors: Populated<DoorPoseData, Or<(Changed<SlidingDoor>, Changed<DoorRuntime>)>>,
+    mut leaves: LeafPoseQuery,
+) {
+    for (mut transform, mut pose_target, door, runtime, door_leaves, path, swing) in
+        doors.iter_mut()
+    {
+        match door_leaves {
+            Some(door_leaves) => pose_leaves(door_leaves, &mut leaves, runtime.progress, false),
+            None => {
+                let pose = door_pose(
+                    &transform,
+                    door.closed_position,
+                    door.open_position,
+                    path,
+                    swing,
+                    runtime.progress,
+                );
+                write_pose(&mut transform, pose_target.as_deref_mut(), pose);
+            }
+        }
+    }
+}
+
+/// Moves a door, and the leaves it already has, straight to the pose matching its progress.
+/// Bodies moved by physics get there too, instead of travelling there during the next physics step
+pub fn snap_door_pose(
+    In(door_entity): In<Entity>,
+    mut doors: Query<DoorPoseData>,
+    mut leaves: LeafPoseQuery,
+) {
+    let Ok((mut transform, mut pose_target, door, runtime, door_leaves, path, swing)) =
+        doors.get_mut(door_entity)
+    else {
+        return;
+    };
+
+    match door_leaves {
+        Some(door_leaves) => {
+            pose_leaves(door_leaves, &mut leaves, runtime.progress, false);
+
+            for leaf_entity in door_leaves.iter() {
+                if let Ok((mut leaf_transform, Some(leaf_pose_target), ..)) =
+                    leaves.get_mut(leaf_entity)
+                {
+                    *leaf_transform = leaf_pose_target.0;
+                }
+            }
+        }
+        None => {
+            let pose = door_pose(
+                &transform,
+                door.closed_position,
+                door.open_position,
+                path,
+                swing,
+                runtime.progress,
+            );
+            write_pose(&mut transform, pose_target.as_deref_mut(), pose);
+            *transform = pose;
+        }
+    }
+}
+
+/// Records the progress of every door before the door logic runs, so their pose can be interpolated
+pub fn store_previous_progress(mut doors: Query<(&DoorRuntime, &mut DoorPreviousProgress)>) {
+    for (runtime, mut previous_progress) in doors.iter_mut() {
+        previous_progress.set_if_neq(DoorPreviousProgress(runtime.progress));
+    }
+}
+
+/// Moves doors to a pose in between the last two runs of the door logic, based on how far along the current fixed timestep is.
+/// Used on top of [`update_door_poses`] when the door logic runs in a fixed timestep, so that doors don't stutter.
+/// Doors and leaves with a [`DoorPoseTarget`] are left alone, since whatever moves them (like physics) only does so on its own steps
+pub fn interpolate_door_poses(
+    mut doors: InterpolatedDoorQuery,
+    mut leaves: LeafPoseQuery,
+    fixed_time: Res<Time<Fixed>>,
+) {
+    let overstep = fixed_time.overstep_fraction();
+
+    for (
+        mut transform,
+        has_pose_target,
+        door,
+        runtime,
+        previous_progress,
+        door_leaves,
+        path,
+        swing,
+    ) in doors.iter_mut()
+    {
+        // doors that are standing still don't need to be moved again
+        if previous_progress.0 == runtime.progress
+            && !door.is_changed()
+            && !runtime.is_changed()
+            && !previous_progress.is_changed()
+        {
+            continue;
+        }
+
+        let t = previous_progress.0.lerp(runtime.progress, overstep);
+
+        match door_leaves {
+            Some(door_leaves) => pose_leaves(door_leaves, &mut leaves, t, true),
+            None if has_pose_target => {}
+            None => {
+                *transform = door_pose(
+                    &transform,
+                    door.closed_position,
+                    door.open_position,
+                    path,
+                    swing,
+                    t,
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,145 @@
+use std::any::TypeId;
+use std::sync::Arc;
+
+use avian3d::prelude::*;
+use bevy::ecs::entity::EntityHashSet;
+use bevy::prelude::*;
+
+use crate::{RequestClose, RequestOpen};
+
+/// Which bodies a [`DoorSensor`] reacts to
+#[derive(Clone, Default)]
+pub enum DoorSensorFilter {
+    /// any body
+    #[default]
+    Any,
+    /// bodies that have the component with this `TypeId`, like a player marker. see [`DoorSensorFilter::component`]
+    Component(TypeId),
+    /// colliders whose `CollisionLayers` memberships are in this mask
+    Layers(LayerMask),
+    /// bodies the function returns true for
+    Predicate(Arc<dyn Fn(EntityRef) -> bool + Send + Sync>),
+}
+
+impl DoorSensorFilter {
+    /// Only bodies that have the component `C`
+    pub fn component<C: Component>() -> Self {
+        Self::Component(TypeId::of::<C>())
+    }
+
+    pub fn predicate(predicate: impl Fn(EntityRef) -> bool + Send + Sync + 'static) -> Self {
+        Self::Predicate(Arc::new(predicate))
+    }
+
+    pub fn allows(&self, collider: EntityRef, body: EntityRef) -> bool {
+        match self {
+            Self::Any => true,
+            Self::Component(type_id) => body.contains_type_id(*type_id),
+            Self::Layers(mask) => collider
+                .get::<CollisionLayers>()
+                .is_some_and(|layers| layers.memberships & *mask != LayerMask::NONE),
+            Self::Predicate(predicate) => predicate(body),
+        }
+    }
+}
+
+/// Sensor collider that asks its doors to open for every body it allows that enters, and to close once that body leaves.
+/// The [`DoorOccupancy`](crate::DoorOccupancy) of each door keeps it open until all of them have left
+#[derive(Component, Clone)]
+#[require(Sensor, CollisionEventsEnabled, DoorSensorOccupants)]
+pub struct DoorSensor {
+    /// doors this sensor opens and closes
+    pub doors: Vec<Entity>,
+    pub filter: DoorSensorFilter,
+}
+
+impl DoorSensor {
+    pub fn new(door: Entity) -> Self {
+        Self {
+            doors: vec![door],
+            filter: DoorSensorFilter::default(),
+        }
+    }
+
+    pub fn with_filter(mut self, filter: DoorSensorFilter) -> Self {
+        self.filter = filter;
+        self
+    }
+}
+
+/// Colliders currently inside a [`DoorSensor`] that it allows
+#[derive(Component, Default, Clone)]
+pub struct DoorSensorOccupants(pub EntityHashSet);
+
+pub fn update_door_sensors(
+    mut commands: Commands,
+    mut started: EventReader<CollisionStarted>,
+    mut ended: EventReader<CollisionEnded>,
+    mut sensors: Query<(&DoorSensor, &mut DoorSensorOccupants)>,
+    colliders: Query<&ColliderOf>,
+    entities: Query<EntityRef, Without<DoorSensor>>,
+) {
+    for CollisionStarted(entity1, entity2) in started.read() {
+        // either of the two can be the sensor
+        for (sensor_entity, collider_entity) in [(*entity1, *entity2), (*entity2, *entity1)] {
+            let Ok((sensor, mut occupants)) = sensors.get_mut(sensor_entity) else {
+                continue;
+            };
+
+            let body_entity = colliders
+                .get(collider_entity)
+                .map_or(collider_entity, |collider_of| collider_of.body);
+
+            let (Ok(collider), Ok(body)) =
+                (entities.get(collider_entity), entities.get(body_entity))
+            else {
+                continue;
+            };
+
+            // other sensors are not bodies that can walk through a door
+            if collider.contains::<Sensor>() || !sensor.filter.allows(collider, body) {
+                continue;
+            }
+
+            // the doors count who is inside, a body entering again only counts once
+            if occupants.0.insert(collider_entity) {
+                for &door in &sensor.doors {
+                    commands.trigger_targets(RequestOpen::by(body_entity), door);
+                }
+            }
+        }
+    }
+
+    for CollisionEnded(entity1, entity2) in ended.read() {
+        for (sensor_entity, collider_entity) in [(*entity1, *entity2), (*entity2, *entity1)] {
+            let Ok((sensor, mut occupants)) = sensors.get_mut(sensor_entity) else {
+                continue;
+            };
+
+            // colliders that were never let in are not in the set
+            if !occupants.0.remove(&collider_entity) {
+                continue;
+            }
+
+            let body_of = |collider_entity: Entity| {
+                colliders
+                    .get(collider_entity)
+                    .map_or(collider_entity, |collider_of| collider_of.body)
+            };
+            let body_entity = body_of(collider_entity);
+
+            // a body with several colliders is still inside until the last of them leaves
+            if occupants
+                .0
+                .iter()
+                .any(|&occupant| body_of(occupant) == body_entity)
+            {
+                continue;
+            }
+
+            for &door in &sensor.doors {
+                commands.trigger_targets(RequestClose::by(body_entity), door);
+            }
+        }
+    }
+}
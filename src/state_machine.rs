@@ -2,20 +2,119 @@ use bevy::prelude::*;
 use bevy_gearbox::prelude::*;
 use bevy_gearbox::transitions::Source;
 
-use crate::SlidingDoor;
+use crate::{snap_door_pose, DoorPreviousProgress, DoorRuntime, SlidingDoor};
 
 // --- Events ---
 
 // these events are to be used from outside of the plugin
 
+#[derive(Event, Clone, Default)]
+pub struct RequestOpen {
+    /// who asked for the door to open, checked against the [`DoorAccess`](crate::DoorAccess) of the door
+    pub instigator: Option<Entity>,
+}
+
+impl RequestOpen {
+    pub fn by(instigator: Entity) -> Self {
+        Self {
+            instigator: Some(instigator),
+        }
+    }
+}
+
+#[derive(Event, Clone, Default)]
+pub struct RequestClose {
+    /// who asked for the door to close
+    pub instigator: Option<Entity>,
+}
+
+impl RequestClose {
+    pub fn by(instigator: Entity) -> Self {
+        Self {
+            instigator: Some(instigator),
+        }
+    }
+}
+
+/// Moves the door to a fraction of the way open (0.0 is closed, 1.0 is open), and holds it there.
+/// A NaN fraction is ignored
+#[derive(Event, Clone)]
+pub struct RequestPartialOpen {
+    pub fraction: f32,
+    /// who asked for the door to open, checked against the [`DoorAccess`](crate::DoorAccess) of the door
+    pub instigator: Option<Entity>,
+}
+
+impl RequestPartialOpen {
+    pub fn new(fraction: f32) -> Self {
+        Self {
+            fraction,
+            instigator: None,
+        }
+    }
+
+    pub fn with_instigator(mut self, instigator: Entity) -> Self {
+        self.instigator = Some(instigator);
+        self
+    }
+}
+
+/// Freezes a moving door where it currently is
+#[derive(Event, Clone)]
+pub struct RequestStop;
+
+/// Makes a stopped door continue towards where it was going
+#[derive(Event, Clone, Default)]
+pub struct RequestResume {
+    /// who asked for the door to resume, passed on to the request it turns into
+    pub instigator: Option<Entity>,
+}
+
+impl RequestResume {
+    pub fn by(instigator: Entity) -> Self {
+        Self {
+            instigator: Some(instigator),
+        }
+    }
+}
+
+/// Single button control: opens a closed door, stops a moving door,
+/// sends a stopped door the opposite way it was going, and closes an open door
+#[derive(Event, Clone, Default)]
+pub struct RequestToggle {
+    /// who pressed the button, passed on to the request it turns into
+    pub instigator: Option<Entity>,
+}
+
+impl RequestToggle {
+    pub fn by(instigator: Entity) -> Self {
+        Self {
+            instigator: Some(instigator),
+        }
+    }
+}
+
+/// Locks the door, so that it ignores requests to open. A door that isn't closed gets locked once it has closed
 #[derive(Event, Clone)]
-pub struct RequestOpen;
+pub struct RequestLock;
 
 #[derive(Event, Clone)]
-pub struct RequestClose;
+pub struct RequestUnlock;
 
 // these events are internal to the plugin, emited when animations/countdowns finish
 
+/// a door only starts opening once whoever asked for it is allowed to, see [`check_door_access`](crate::check_door_access)
+#[derive(Event, Clone)]
+pub struct AccessGranted;
+
+/// same as [`AccessGranted`] for [`RequestPartialOpen`], with the fraction the door moves to
+#[derive(Event, Clone)]
+pub struct PartialOpenGranted(pub f32);
+
+/// a door only starts closing once no one wants it open, see [`check_door_occupancy`](crate::check_door_occupancy)
+#[derive(Event, Clone)]
+pub struct CloseGranted;
+
 #[derive(Event, Clone)]
 pub struct FinishedOpening;
 
@@ -25,6 +124,9 @@ pub struct FinishedClosing;
 #[derive(Event, Clone)]
 pub struct FinishedWaiting;
 
+#[derive(Event, Clone)]
+pub struct FinishedMovingToPartial;
+
 // --- State Marker Components ---
 
 /// Marker component for when the door is closed
@@ -43,56 +145,257 @@ pub struct DoorOpen;
 #[derive(Component, Clone)]
 pub struct DoorClosing;
 
+/// Marker component for when the door is moving to a partially open position
+#[derive(Component, Clone)]
+pub struct DoorMovingToPartial;
+
+/// Marker component for when the door is held partially open
+#[derive(Component, Clone)]
+pub struct DoorPartiallyOpen;
+
+/// Marker component for when the door was stopped while moving
+#[derive(Component, Clone)]
+pub struct DoorStopped;
+
+/// Marker component for when the door is closed and locked
+#[derive(Component, Clone)]
+pub struct DoorLocked;
+
 /// Marker component for when the door is waiting before starting to close
 #[derive(Component, Clone, Default)]
 pub struct DoorWaiting {
     pub waiting_for_secs: f32,
 }
 
+/// How a door behaves once it is open. Read when the door is spawned, doors without it are [`DoorMode::Latching`]
+#[derive(Component, Clone, Copy, Default, PartialEq, Eq, Debug)]
+pub enum DoorMode {
+    /// the door closes by itself `waiting_secs` after it finished opening and no one keeps it open (see [`DoorOccupancy`](crate::DoorOccupancy)).
+    /// opening it again restarts the wait
+    AutoClose,
+    /// the door stays open until it is asked to close, and then waits `waiting_secs` before closing
+    #[default]
+    Latching,
+    /// the door only moves while it keeps getting [`RequestOpen`] or [`RequestClose`] every frame, and stops as soon as they stop coming
+    HoldToRun,
+}
+
+/// State a door starts in when it is spawned, snapping it to the matching pose. Doors without it start closed
+#[derive(Component, Clone, Copy, Default, PartialEq, Debug)]
+pub enum DoorInitialState {
+    #[default]
+    Closed,
+    Open,
+    /// held partially open at this fraction of the way, like after [`RequestPartialOpen`]. a NaN fraction starts the door closed
+    Partial(f32),
+    Locked,
+}
+
+/// Ties a state or transition of the state machine of a door to the door, so it gets despawned along with it
+#[derive(Component)]
+#[relationship(relationship_target = DoorStateParts)]
+pub struct DoorStatePartOf(pub Entity);
+
+/// States and transitions making up the state machine of this door
+#[derive(Component)]
+#[relationship_target(relationship = DoorStatePartOf, linked_spawn)]
+pub struct DoorStateParts(Vec<Entity>);
+
+/// Puts the state machine of this door on an entity of its own, instead of on the door entity.
+/// Useful when the door already has a state machine of its own. The state marker components are still mirrored onto the door,
+/// and requests sent to the door are forwarded to its state machine
+#[derive(Component, Clone, Copy, Default)]
+pub struct SeparateDoorMachine;
+
+/// Entity holding the state machine of a door with [`SeparateDoorMachine`]
+#[derive(Component)]
+#[relationship(relationship_target = DoorMachine)]
+pub struct DoorMachineOf(pub Entity);
+
+/// State machine of a door with [`SeparateDoorMachine`]
+#[derive(Component)]
+#[relationship_target(relationship = DoorMachineOf)]
+pub struct DoorMachine(Entity);
+
+impl DoorMachine {
+    pub fn machine(&self) -> Entity {
+        self.0
+    }
+}
+
+/// Requests and internal events are sent to the door, so they have to be passed on to a separate state machine
+pub fn forward_to_door_machine<E: Event + Clone>(
+    trigger: Trigger<E>,
+    mut commands: Commands,
+    doors: Query<&DoorMachine>,
+) {
+    if let Ok(door_machine) = doors.get(trigger.target()) {
+        commands.trigger_targets(trigger.event().clone(), door_machine.machine());
+    }
+}
+
+pub fn mirror_state_component<C: Component + Clone>(
+    trigger: Trigger<OnAdd, C>,
+    mut commands: Commands,
+    machines: Query<(&C, &DoorMachineOf)>,
+) {
+    if let Ok((component, machine_of)) = machines.get(trigger.target()) {
+        commands.entity(machine_of.0).try_insert(component.clone());
+    }
+}
+
+pub fn unmirror_state_component<C: Component>(
+    trigger: Trigger<OnRemove, C>,
+    mut commands: Commands,
+    machines: Query<&DoorMachineOf, With<C>>,
+) {
+    if let Ok(machine_of) = machines.get(trigger.target()) {
+        commands.entity(machine_of.0).try_remove::<C>();
+    }
+}
+
+/// Requests that arrived before the state machine of the door was created, replayed once it is.
+/// Managed by the plugin, the door only reacts to requests once this is gone
+#[derive(Component, Default)]
+pub struct DoorEarlyRequests(Vec<Box<dyn FnOnce(&mut World) + Send + Sync>>);
+
+/// The state machine is created a bit after the door is spawned, so requests sent right after spawning it would get lost.
+/// The other observers of the requests skip doors that still have [`DoorEarlyRequests`], and handle them on replay instead
+pub(crate) fn buffer_early_request<E: Event + Clone>(
+    trigger: Trigger<E>,
+    mut doors: Query<&mut DoorEarlyRequests>,
+) {
+    let door_entity = trigger.target();
+
+    if let Ok(mut early_requests) = doors.get_mut(door_entity) {
+        let event = trigger.event().clone();
+        early_requests.0.push(Box::new(move |world: &mut World| {
+            world.trigger_targets(event, door_entity);
+        }));
+    }
+}
+
 /// Hook to automatically create the state machine on sliding door entities
 pub fn create_door_state_machine(trigger: Trigger<OnAdd, SlidingDoor>, mut commands: Commands) {
     let door_entity = trigger.target();
 
     commands.queue(move |world: &mut World| {
+        let mode = world
+            .get::<DoorMode>(door_entity)
+            .copied()
+            .unwrap_or_default();
+        let initial_state = world
+            .get::<DoorInitialState>(door_entity)
+            .copied()
+            .unwrap_or_default();
+
         // Create state entities - we need intermediate states to defer events
-        // let machine_entity = world.spawn(()).id();
-        let machine_entity = door_entity; // the entity already exists, no need to make a new one
-        let closed = world.spawn(()).id();
-        let opening = world.spawn(()).id();
-        let open = world.spawn(()).id();
-        let closing = world.spawn(()).id();
-        let waiting = world.spawn(()).id();
+        let machine_entity = if world.entity(door_entity).contains::<SeparateDoorMachine>() {
+            world
+                .spawn((DoorStatePartOf(door_entity), DoorMachineOf(door_entity)))
+                .id()
+        } else {
+            door_entity // the entity already exists, no need to make a new one
+        };
+        let closed = world.spawn(DoorStatePartOf(door_entity)).id();
+        let opening = world.spawn(DoorStatePartOf(door_entity)).id();
+        let open = world.spawn(DoorStatePartOf(door_entity)).id();
+        let closing = world.spawn(DoorStatePartOf(door_entity)).id();
+        let waiting = world.spawn(DoorStatePartOf(door_entity)).id();
+        let moving_to_partial = world.spawn(DoorStatePartOf(door_entity)).id();
+        let partial = world.spawn(DoorStatePartOf(door_entity)).id();
+        let stopped = world.spawn(DoorStatePartOf(door_entity)).id();
+        let locked = world.spawn(DoorStatePartOf(door_entity)).id();
 
         // Create transition entities
-        let closed_to_opening = world.spawn(()).id();
-        let opening_to_open = world.spawn(()).id();
-        let open_to_waiting = world.spawn(()).id();
-        let waiting_to_closing = world.spawn(()).id();
-        let closing_to_closed = world.spawn(()).id();
-        let closing_to_opening = world.spawn(()).id();
-        let waiting_to_open = world.spawn(()).id();
+        let closed_to_opening = world.spawn(DoorStatePartOf(door_entity)).id();
+        let opening_to_open = world.spawn(DoorStatePartOf(door_entity)).id();
+        let open_to_waiting = world.spawn(DoorStatePartOf(door_entity)).id();
+        let waiting_to_closing = world.spawn(DoorStatePartOf(door_entity)).id();
+        let closing_to_closed = world.spawn(DoorStatePartOf(door_entity)).id();
+        let closing_to_opening = world.spawn(DoorStatePartOf(door_entity)).id();
+        let waiting_to_open = world.spawn(DoorStatePartOf(door_entity)).id();
+        let moving_to_partial_to_partial = world.spawn(DoorStatePartOf(door_entity)).id();
+        let partial_to_opening = world.spawn(DoorStatePartOf(door_entity)).id();
+        let partial_to_closing = world.spawn(DoorStatePartOf(door_entity)).id();
+        let moving_to_partial_to_opening = world.spawn(DoorStatePartOf(door_entity)).id();
+        let moving_to_partial_to_closing = world.spawn(DoorStatePartOf(door_entity)).id();
+        let stopped_to_opening = world.spawn(DoorStatePartOf(door_entity)).id();
+        let stopped_to_closing = world.spawn(DoorStatePartOf(door_entity)).id();
+        let closed_to_locked = world.spawn(DoorStatePartOf(door_entity)).id();
+        let locked_to_closed = world.spawn(DoorStatePartOf(door_entity)).id();
+        // RequestStop interrupts any movement
+        let to_stopped = [
+            (opening, "Opening"),
+            (closing, "Closing"),
+            (moving_to_partial, "MovingToPartial"),
+        ]
+        .map(|(source, source_name)| {
+            (
+                source,
+                source_name,
+                world.spawn(DoorStatePartOf(door_entity)).id(),
+            )
+        });
+        // RequestPartialOpen interrupts anything, once it is granted
+        let to_moving_to_partial = [
+            (closed, "Closed"),
+            (opening, "Opening"),
+            (open, "Open"),
+            (closing, "Closing"),
+            (waiting, "Waiting"),
+            (partial, "Partial"),
+            (stopped, "Stopped"),
+        ]
+        .map(|(source, source_name)| {
+            (
+                source,
+                source_name,
+                world.spawn(DoorStatePartOf(door_entity)).id(),
+            )
+        });
+
+        let (initial, initial_progress) = match initial_state {
+            DoorInitialState::Closed => (closed, 0.0),
+            // an auto-closing door that starts open is already waiting to close
+            DoorInitialState::Open if mode == DoorMode::AutoClose => (waiting, 1.0),
+            DoorInitialState::Open => (open, 1.0),
+            DoorInitialState::Partial(fraction) if fraction.is_nan() => (closed, 0.0),
+            DoorInitialState::Partial(fraction) => (partial, fraction.clamp(0.0, 1.0)),
+            DoorInitialState::Locked => (locked, 0.0),
+        };
 
         // Set up the machine root
-        world.entity_mut(machine_entity).insert((
-            Name::new("DoorStateMachine"),
-            StateMachine::new(),
-            InitialState(closed),
-        ));
+        world
+            .entity_mut(machine_entity)
+            .insert((StateMachine::new(), InitialState(initial)));
+        // don't replace the name of a door that already has one
+        if !world.entity(machine_entity).contains::<Name>() {
+            world
+                .entity_mut(machine_entity)
+                .insert(Name::new("DoorStateMachine"));
+        }
 
         // Set up states with marker components
         world.entity_mut(closed).insert((
             Name::new("Closed"),
             StateChildOf(machine_entity),
-            // StateComponent(DoorClosed),
+            StateComponent(DoorClosed),
         ));
 
         world.entity_mut(opening).insert((
             Name::new("Opening"),
             StateChildOf(machine_entity),
             StateComponent(DoorOpening), // With<DoorOpening> will tell you doors that are in the DoorOpening state
-            DeferEvent::<RequestClose>::new(), // Defer RequestClose while opening. Once the door finishes opening, it will then start to close (or in this case, go to the Waiting state)
         ));
 
+        // a hold-to-run door turns around right away instead
+        if mode != DoorMode::HoldToRun {
+            world.entity_mut(opening).insert(
+                DeferEvent::<CloseGranted>::new(), // Defer CloseGranted while opening. Once the door finishes opening, it will then start to close (or in this case, go to the Waiting state)
+            );
+        }
+
         world.entity_mut(open).insert((
             Name::new("Open"),
             StateChildOf(machine_entity),
@@ -111,30 +414,91 @@ pub fn create_door_state_machine(trigger: Trigger<OnAdd, SlidingDoor>, mut comma
             StateComponent(DoorWaiting::default()),
         ));
 
+        world.entity_mut(moving_to_partial).insert((
+            Name::new("MovingToPartial"),
+            StateChildOf(machine_entity),
+            StateComponent(DoorMovingToPartial),
+        ));
+
+        world.entity_mut(partial).insert((
+            Name::new("Partial"),
+            StateChildOf(machine_entity),
+            StateComponent(DoorPartiallyOpen),
+        ));
+
+        world.entity_mut(stopped).insert((
+            Name::new("Stopped"),
+            StateChildOf(machine_entity),
+            StateComponent(DoorStopped),
+        ));
+
+        world.entity_mut(locked).insert((
+            Name::new("Locked"),
+            StateChildOf(machine_entity),
+            StateComponent(DoorLocked),
+        ));
+
         // Set up transitions - immediate event-driven transitions, then After delays
         world.entity_mut(closed_to_opening).insert((
-            Name::new("Closed -> Opening (RequestOpen)"),
+            Name::new("Closed -> Opening (AccessGranted)"),
             Target(opening),
-            TransitionListener::<RequestOpen>::default(),
+            TransitionListener::<AccessGranted>::default(),
             TransitionKind::External,
             Source(closed),
         ));
 
-        world.entity_mut(opening_to_open).insert((
-            Name::new("Opening -> Open (FinishedOpening)"),
-            Target(open),
-            TransitionListener::<FinishedOpening>::default(),
-            TransitionKind::External,
-            Source(opening),
-        ));
+        match mode {
+            // an auto-closing door never stays open, it starts waiting to close right away
+            DoorMode::AutoClose => {
+                world.entity_mut(opening_to_open).insert((
+                    Name::new("Opening -> Waiting (FinishedOpening)"),
+                    Target(waiting),
+                    TransitionListener::<FinishedOpening>::default(),
+                    TransitionKind::External,
+                    Source(opening),
+                ));
+            }
+            DoorMode::Latching | DoorMode::HoldToRun => {
+                world.entity_mut(opening_to_open).insert((
+                    Name::new("Opening -> Open (FinishedOpening)"),
+                    Target(open),
+                    TransitionListener::<FinishedOpening>::default(),
+                    TransitionKind::External,
+                    Source(opening),
+                ));
+            }
+        }
 
-        world.entity_mut(open_to_waiting).insert((
-            Name::new("Open -> Waiting (RequestClose)"),
-            Target(waiting),
-            TransitionListener::<RequestClose>::default(),
-            TransitionKind::External,
-            Source(open),
-        ));
+        match mode {
+            // a hold-to-run door has no one to wait for
+            DoorMode::HoldToRun => {
+                world.entity_mut(open_to_waiting).insert((
+                    Name::new("Open -> Closing (CloseGranted)"),
+                    Target(closing),
+                    TransitionListener::<CloseGranted>::default(),
+                    TransitionKind::External,
+                    Source(open),
+                ));
+
+                let opening_to_closing = world.spawn(DoorStatePartOf(door_entity)).id();
+                world.entity_mut(opening_to_closing).insert((
+                    Name::new("Opening -> Closing (CloseGranted)"),
+                    Target(closing),
+                    TransitionListener::<CloseGranted>::default(),
+                    TransitionKind::External,
+                    Source(opening),
+                ));
+            }
+            DoorMode::AutoClose | DoorMode::Latching => {
+                world.entity_mut(open_to_waiting).insert((
+                    Name::new("Open -> Waiting (CloseGranted)"),
+                    Target(waiting),
+                    TransitionListener::<CloseGranted>::default(),
+                    TransitionKind::External,
+                    Source(open),
+                ));
+            }
+        }
 
         world.entity_mut(closing_to_closed).insert((
             Name::new("Closing -> Closed (FinishedClosing)"),
@@ -145,9 +509,9 @@ pub fn create_door_state_machine(trigger: Trigger<OnAdd, SlidingDoor>, mut comma
         ));
 
         world.entity_mut(closing_to_opening).insert((
-            Name::new("Closing -> Opening (RequestOpen)"),
+            Name::new("Closing -> Opening (AccessGranted)"),
             Target(opening),
-            TransitionListener::<RequestOpen>::default(),
+            TransitionListener::<AccessGranted>::default(),
             TransitionKind::External,
             Source(closing),
         ));
@@ -160,12 +524,170 @@ pub fn create_door_state_machine(trigger: Trigger<OnAdd, SlidingDoor>, mut comma
             Source(waiting),
         ));
 
-        world.entity_mut(waiting_to_open).insert((
-            Name::new("Waiting -> Open (RequestOpen)"),
-            Target(open),
-            TransitionListener::<RequestOpen>::default(),
+        // a waiting door is already fully open, so keeping it open doesn't need access
+        match mode {
+            // entering Waiting again restarts the wait
+            DoorMode::AutoClose => {
+                world.entity_mut(waiting_to_open).insert((
+                    Name::new("Waiting -> Waiting (RequestOpen)"),
+                    Target(waiting),
+                    TransitionListener::<RequestOpen>::default(),
+                    TransitionKind::External,
+                    Source(waiting),
+                ));
+            }
+            DoorMode::Latching | DoorMode::HoldToRun => {
+                world.entity_mut(waiting_to_open).insert((
+                    Name::new("Waiting -> Open (RequestOpen)"),
+                    Target(open),
+                    TransitionListener::<RequestOpen>::default(),
+                    TransitionKind::External,
+                    Source(waiting),
+                ));
+            }
+        }
+
+        for (source, source_name, transition) in to_moving_to_partial {
+            world.entity_mut(transition).insert((
+                Name::new(format!(
+                    "{source_name} -> MovingToPartial (PartialOpenGranted)"
+                )),
+                Target(moving_to_partial),
+                TransitionListener::<PartialOpenGranted>::default(),
+                TransitionKind::External,
+                Source(source),
+            ));
+        }
+
+        for (source, source_name, transition) in to_stopped {
+            world.entity_mut(transition).insert((
+                Name::new(format!("{source_name} -> Stopped (RequestStop)")),
+                Target(stopped),
+                TransitionListener::<RequestStop>::default(),
+                TransitionKind::External,
+                Source(source),
+            ));
+        }
+
+        world.entity_mut(stopped_to_opening).insert((
+            Name::new("Stopped -> Opening (AccessGranted)"),
+            Target(opening),
+            TransitionListener::<AccessGranted>::default(),
             TransitionKind::External,
-            Source(waiting),
+            Source(stopped),
+        ));
+
+        // like a partially open door, a stopped door closes right away
+        world.entity_mut(stopped_to_closing).insert((
+            Name::new("Stopped -> Closing (CloseGranted)"),
+            Target(closing),
+            TransitionListener::<CloseGranted>::default(),
+            TransitionKind::External,
+            Source(stopped),
+        ));
+
+        world.entity_mut(moving_to_partial_to_partial).insert((
+            Name::new("MovingToPartial -> Partial (FinishedMovingToPartial)"),
+            Target(partial),
+            TransitionListener::<FinishedMovingToPartial>::default(),
+            TransitionKind::External,
+            Source(moving_to_partial),
+        ));
+
+        world.entity_mut(moving_to_partial_to_opening).insert((
+            Name::new("MovingToPartial -> Opening (AccessGranted)"),
+            Target(opening),
+            TransitionListener::<AccessGranted>::default(),
+            TransitionKind::External,
+            Source(moving_to_partial),
+        ));
+
+        world.entity_mut(moving_to_partial_to_closing).insert((
+            Name::new("MovingToPartial -> Closing (CloseGranted)"),
+            Target(closing),
+            TransitionListener::<CloseGranted>::default(),
+            TransitionKind::External,
+            Source(moving_to_partial),
+        ));
+
+        world.entity_mut(partial_to_opening).insert((
+            Name::new("Partial -> Opening (AccessGranted)"),
+            Target(opening),
+            TransitionListener::<AccessGranted>::default(),
+            TransitionKind::External,
+            Source(partial),
+        ));
+
+        // a partially open door closes right away, since waiting is only meant for fully open doors
+        world.entity_mut(partial_to_closing).insert((
+            Name::new("Partial -> Closing (CloseGranted)"),
+            Target(closing),
+            TransitionListener::<CloseGranted>::default(),
+            TransitionKind::External,
+            Source(partial),
+        ));
+
+        // only a closed door can be locked, see queue_door_lock for the others
+        world.entity_mut(closed_to_locked).insert((
+            Name::new("Closed -> Locked (RequestLock)"),
+            Target(locked),
+            TransitionListener::<RequestLock>::default(),
+            TransitionKind::External,
+            Source(closed),
+        ));
+
+        world.entity_mut(locked_to_closed).insert((
+            Name::new("Locked -> Closed (RequestUnlock)"),
+            Target(closed),
+            TransitionListener::<RequestUnlock>::default(),
+            TransitionKind::External,
+            Source(locked),
         ));
+
+        // the door starts where its initial state says, instead of moving there
+        if let Some(mut runtime) = world.get_mut::<DoorRuntime>(door_entity) {
+            runtime.progress = initial_progress;
+            runtime.partial_target = initial_progress;
+        }
+        world
+            .entity_mut(door_entity)
+            .insert(DoorPreviousProgress(initial_progress));
+        let _ = world.run_system_cached_with(snap_door_pose, door_entity);
+
+        // everything is ready now, in the order the requests were sent
+        if let Some(early_requests) = world.entity_mut(door_entity).take::<DoorEarlyRequests>() {
+            for request in early_requests.0 {
+                request(world);
+            }
+        }
+    });
+}
+
+/// Tears down the state machine of an entity that stops being a door. Despawned doors take their states and transitions with them already
+pub fn remove_door_state_machine(trigger: Trigger<OnRemove, SlidingDoor>, mut commands: Commands) {
+    let door_entity = trigger.target();
+
+    commands.queue(move |world: &mut World| {
+        let Ok(mut door) = world.get_entity_mut(door_entity) else {
+            return;
+        };
+
+        // a separate machine is one of the parts, and the machine on the door may not be ours
+        if !door.contains::<SeparateDoorMachine>() {
+            door.remove::<(StateMachine, InitialState)>();
+        }
+        door.despawn_related::<DoorStateParts>();
+        door.remove::<(
+            DoorClosed,
+            DoorOpening,
+            DoorOpen,
+            DoorClosing,
+            DoorWaiting,
+            DoorMovingToPartial,
+            DoorPartiallyOpen,
+            DoorStopped,
+            DoorLocked,
+            DoorEarlyRequests,
+        )>();
     });
 }
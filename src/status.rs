@@ -0,0 +1,208 @@
+use bevy::prelude::*;
+
+use crate::*;
+
+/// What the door is currently doing, kept up to date from its state machine
+#[derive(Component, Clone, Copy, Default, PartialEq, Eq, Debug)]
+pub enum DoorStatus {
+    #[default]
+    Closed,
+    Opening,
+    Open,
+    Closing,
+    Waiting,
+    MovingToPartial,
+    PartiallyOpen,
+    Stopped,
+    Locked,
+}
+
+/// How far open the door is, from 0 (closed) to 1 (open). Easings that overshoot can briefly take it past either end
+#[derive(Component, Clone, Copy, Default, PartialEq, Debug)]
+pub struct DoorProgress(pub f32);
+
+// these events are triggered on the door, and can also be read with an `EventReader`
+
+#[derive(Event, Clone, Debug)]
+pub struct DoorStartedOpening {
+    pub door: Entity,
+}
+
+/// The door got fully open
+#[derive(Event, Clone, Debug)]
+pub struct DoorOpened {
+    pub door: Entity,
+}
+
+#[derive(Event, Clone, Debug)]
+pub struct DoorStartedClosing {
+    pub door: Entity,
+}
+
+/// The door got fully closed
+#[derive(Event, Clone, Debug)]
+pub struct DoorFinishedClosing {
+    pub door: Entity,
+}
+
+/// The door is open and started waiting before closing
+#[derive(Event, Clone, Debug)]
+pub struct DoorWaitStarted {
+    pub door: Entity,
+}
+
+/// The door turned around while it was moving
+#[derive(Event, Clone, Debug)]
+pub struct DoorReversed {
+    pub door: Entity,
+    /// true if the door is now opening, false if it is now closing
+    pub opening: bool,
+}
+
+/// State marker components, and the status of the door they stand for
+pub trait DoorStateMarker: Component {
+    const STATUS: DoorStatus;
+}
+
+impl DoorStateMarker for DoorClosed {
+    const STATUS: DoorStatus = DoorStatus::Closed;
+}
+
+impl DoorStateMarker for DoorOpening {
+    const STATUS: DoorStatus = DoorStatus::Opening;
+}
+
+impl DoorStateMarker for DoorOpen {
+    const STATUS: DoorStatus = DoorStatus::Open;
+}
+
+impl DoorStateMarker for DoorClosing {
+    const STATUS: DoorStatus = DoorStatus::Closing;
+}
+
+impl DoorStateMarker for DoorWaiting {
+    const STATUS: DoorStatus = DoorStatus::Waiting;
+}
+
+impl DoorStateMarker for DoorMovingToPartial {
+    const STATUS: DoorStatus = DoorStatus::MovingToPartial;
+}
+
+impl DoorStateMarker for DoorPartiallyOpen {
+    const STATUS: DoorStatus = DoorStatus::PartiallyOpen;
+}
+
+impl DoorStateMarker for DoorStopped {
+    const STATUS: DoorStatus = DoorStatus::Stopped;
+}
+
+impl DoorStateMarker for DoorLocked {
+    const STATUS: DoorStatus = DoorStatus::Locked;
+}
+
+fn emit<E: Event + Clone>(commands: &mut Commands, event: E, door: Entity) {
+    commands.send_event(event.clone());
+    commands.trigger_targets(event, door);
+}
+
+pub fn enter_door_status<C: DoorStateMarker>(
+    trigger: Trigger<OnAdd, C>,
+    mut commands: Commands,
+    mut doors: Query<(&mut DoorStatus, &DoorRuntime)>,
+) {
+    let door_entity = trigger.target();
+
+    let Ok((mut status, runtime)) = doors.get_mut(door_entity) else {
+        return;
+    };
+    *status = C::STATUS;
+
+    // the motion is still the one from before the door changed state
+    let was_opening = runtime
+        .motion
+        .as_ref()
+        .map(|motion| motion.to > motion.from);
+
+    match C::STATUS {
+        DoorStatus::Opening => {
+            if was_opening == Some(false) {
+                emit(
+                    &mut commands,
+                    DoorReversed {
+                        door: door_entity,
+                        opening: true,
+                    },
+                    door_entity,
+                );
+            }
+            emit(
+                &mut commands,
+                DoorStartedOpening { door: door_entity },
+                door_entity,
+            );
+        }
+        DoorStatus::Closing => {
+            if was_opening == Some(true) {
+                emit(
+                    &mut commands,
+                    DoorReversed {
+                        door: door_entity,
+                        opening: false,
+                    },
+                    door_entity,
+                );
+            }
+            emit(
+                &mut commands,
+                DoorStartedClosing { door: door_entity },
+                door_entity,
+            );
+        }
+        DoorStatus::Waiting => {
+            emit(
+                &mut commands,
+                DoorWaitStarted { door: door_entity },
+                door_entity,
+            );
+        }
+        _ => {}
+    }
+}
+
+/// Auto-closing doors never get to the Open state, so this is known from the animation finishing instead
+pub fn door_opened(
+    trigger: Trigger<FinishedOpening>,
+    mut commands: Commands,
+    doors: Query<(), With<DoorStatus>>,
+) {
+    let door_entity = trigger.target();
+
+    if doors.contains(door_entity) {
+        emit(&mut commands, DoorOpened { door: door_entity }, door_entity);
+    }
+}
+
+/// Doors also get to the Closed state by being unlocked, so this is known from the animation finishing instead
+pub fn door_closed(
+    trigger: Trigger<FinishedClosing>,
+    mut commands: Commands,
+    doors: Query<(), With<DoorStatus>>,
+) {
+    let door_entity = trigger.target();
+
+    if doors.contains(door_entity) {
+        emit(
+            &mut commands,
+            DoorFinishedClosing { door: door_entity },
+            door_entity,
+        );
+    }
+}
+
+pub fn update_door_progress(
+    mut doors: Query<(&DoorRuntime, &mut DoorProgress), Changed<DoorRuntime>>,
+) {
+    for (runtime, mut progress) in doors.iter_mut() {
+        progress.set_if_neq(DoorProgress(runtime.progress));
+    }
+}
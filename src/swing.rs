@@ -0,0 +1,143 @@
+use bevy::prelude::*;
+use bevy::transform::helper::TransformHelper;
+
+use crate::{DoorEarlyRequests, DoorLeaves, DoorRuntime, RequestOpen};
+
+/// Makes the door rotate around a hinge while it opens and closes, on top of sliding.
+/// For a door that only swings, give its [`SlidingDoor`] the same `closed_position` and `open_position`
+#[derive(Component, Clone)]
+pub struct SwingDoor {
+    /// point the hinge goes through, in the door's own local space (for example, the edge of its mesh)
+    pub pivot: Vec3,
+    /// axis the door rotates around, in the same space as the door's positions
+    pub axis: Dir3,
+    /// rotation of the door before any swinging is applied
+    pub base_rotation: Quat,
+    /// angle around the hinge when fully closed, in radians
+    pub closed_angle: f32,
+    /// angle around the hinge when fully open, in radians
+    pub open_angle: f32,
+    /// distance from the hinge to the far edge of the door, the edge that travels the furthest.
+    /// with [`DoorTiming::Speed`](crate::DoorTiming::Speed), the speed is how fast this edge moves
+    pub radius: f32,
+    /// if true, the door swings to the other side, mirroring `open_angle` around `closed_angle`.
+    /// set by [`RequestOpenAwayFrom`], or manually while the door is closed
+    pub reversed: bool,
+}
+
+impl Default for SwingDoor {
+    fn default() -> Self {
+        Self {
+            pivot: Vec3::ZERO,
+            axis: Dir3::Y,
+            base_rotation: Quat::IDENTITY,
+            closed_angle: 0.0,
+            open_angle: 90.0_f32.to_radians(),
+            radius: 1.0,
+            reversed: false,
+        }
+    }
+}
+
+impl SwingDoor {
+    /// angle the door is at when it is fully open, taking the swing side into account
+    pub fn target_open_angle(&self) -> f32 {
+        if self.reversed {
+            2.0 * self.closed_angle - self.open_angle
+        } else {
+            self.open_angle
+        }
+    }
+}
+
+/// Opens a door, picking the side a [`SwingDoor`] swings to so that it moves away from the given world position.
+/// For a door with leaves, each leaf with a [`SwingDoor`] picks its own side.
+/// The side can only change while the door is fully closed, otherwise this behaves like [`RequestOpen`]
+#[derive(Event, Clone)]
+pub struct RequestOpenAwayFrom {
+    /// world position the door opens away from
+    pub position: Vec3,
+    /// who asked for the door to open, checked against the [`DoorAccess`](crate::DoorAccess) of the door
+    pub instigator: Option<Entity>,
+}
+
+impl RequestOpenAwayFrom {
+    pub fn new(position: Vec3) -> Self {
+        Self {
+            position,
+            instigator: None,
+        }
+    }
+
+    pub fn with_instigator(mut self, instigator: Entity) -> Self {
+        self.instigator = Some(instigator);
+        self
+    }
+}
+
+/// true if the door (or leaf) has to swing to the reversed side to move away from `position`
+fn swings_towards(
+    swing: &SwingDoor,
+    transform: &Transform,
+    global_transform: &GlobalTransform,
+    position: Vec3,
+) -> bool {
+    let (_, world_rotation, door_center) = global_transform.to_scale_rotation_translation();
+    // the axis is in the parent's space, so undo the door's own rotation to get the parent's rotation
+    let world_axis = world_rotation * transform.rotation.inverse() * swing.axis.as_vec3();
+    let hinge = global_transform.transform_point(swing.pivot);
+
+    // direction the center of the door starts moving towards when opening without reversing
+    let opening_sign = (swing.open_angle - swing.closed_angle).signum();
+    let movement = world_axis.cross(door_center - hinge) * opening_sign;
+
+    movement.dot(door_center - position) < 0.0
+}
+
+pub fn pick_swing_side(
+    trigger: Trigger<RequestOpenAwayFrom>,
+    mut commands: Commands,
+    doors: Query<(&DoorRuntime, Option<&DoorLeaves>)>,
+    early_doors: Query<(), With<DoorEarlyRequests>>,
+    mut swings: Query<(&mut SwingDoor, &Transform)>,
+    transform_helper: TransformHelper,
+) {
+    let door_entity = trigger.target();
+
+    // the request gets replayed once the door is ready
+    if early_doors.contains(door_entity) {
+        return;
+    }
+
+    if let Ok((runtime, door_leaves)) = doors.get(door_entity)
+        && runtime.progress <= 0.0
+    {
+        // each leaf of a door (like both halves of a double door) swings around its own hinge
+        let swinging = std::iter::once(door_entity).chain(
+            door_leaves
+                .into_iter()
+                .flat_map(|door_leaves| door_leaves.iter()),
+        );
+
+        for entity in swinging {
+            // a door that was just spawned doesn't have its GlobalTransform propagated yet
+            if let Ok((mut swing, transform)) = swings.get_mut(entity)
+                && let Ok(global_transform) = transform_helper.compute_global_transform(entity)
+            {
+                swing.reversed = swings_towards(
+                    &swing,
+                    transform,
+                    &global_transform,
+                    trigger.event().position,
+                );
+            }
+        }
+    }
+
+    commands.trigger_targets(
+        RequestOpen {
+            instigator: trigger.event().instigator,
+        },
+        door_entity,
+    );
+}
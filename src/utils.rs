@@ -1,12 +1,216 @@
-use bevy::math::FloatExt;
-use interpolation::Ease;
+use bevy::ecs::{query::Without, system::Query};
+use bevy::math::{Quat, Vec3};
+use bevy::transform::components::Transform;
 
-use crate::SlidingDoor;
+use crate::{
+    DoorLeaf, DoorLeaves, DoorMotion, DoorPath, DoorPoseTarget, DoorRuntime, SlidingDoor,
+    SwingDoor, TelescopingPanel,
+};
 
-pub fn slide_interpolate(door: &SlidingDoor) -> f32 {
-    let t = (door.current_duration_secs / door.target_duration_secs).clamp(0.0, 1.0);
+/// leaves, as needed to know how far they travel
+pub type LeafTravelQuery<'w, 's> = Query<
+    'w,
+    's,
+    (
+        &'static DoorLeaf,
+        Option<&'static TelescopingPanel>,
+        Option<&'static DoorPath>,
+        Option<&'static SwingDoor>,
+    ),
+    Without<SlidingDoor>,
+>;
 
-    let eased_t = Ease::quadratic_in_out(t);
+/// how far a door or leaf travels from closed to open
+pub fn travel_distance(
+    closed: Vec3,
+    open: Vec3,
+    path: Option<&DoorPath>,
+    swing: Option<&SwingDoor>,
+) -> f32 {
+    let slide = path.map_or(closed.distance(open), DoorPath::length);
 
-    door.start_x.lerp(door.end_x, eased_t)
+    // length of the arc described by the far edge of the door around the hinge
+    let arc = swing.map_or(0.0, |swing| {
+        swing.radius * (swing.open_angle - swing.closed_angle).abs()
+    });
+
+    slide + arc
+}
+
+/// how far a door travels from closed to open. a door with leaves travels as far as its furthest travelling leaf,
+/// telescoping panels only travelling their part of the way
+pub fn door_travel_distance(
+    door: &SlidingDoor,
+    door_leaves: Option<&DoorLeaves>,
+    path: Option<&DoorPath>,
+    swing: Option<&SwingDoor>,
+    leaves: &LeafTravelQuery,
+) -> f32 {
+    match door_leaves {
+        Some(door_leaves) => door_leaves
+            .iter()
+            .filter_map(|leaf_entity| leaves.get(leaf_entity).ok())
+            .map(|(leaf, panel, leaf_path, leaf_swing)| {
+                travel_distance(
+                    leaf.closed_position,
+                    leaf.open_position,
+                    leaf_path,
+                    leaf_swing,
+                ) * panel.map_or(1.0, |panel| panel.travel.abs())
+            })
+            .fold(0.0, f32::max),
+        None => travel_distance(door.closed_position, door.open_position, path, swing),
+    }
+}
+
+/// moves the door's progress towards `target`, planning a new motion from the current progress and velocity if it was heading somewhere else.
+/// returns true once the door gets there
+pub fn advance_door(
+    door: &SlidingDoor,
+    runtime: &mut DoorRuntime,
+    target: f32,
+    travel_distance: f32,
+    delta_secs: f32,
+) -> bool {
+    // a blocked door starts its motion over once it is free to move again
+    if runtime.blocked {
+        runtime.velocity = 0.0;
+        runtime.motion = None;
+        return false;
+    }
+
+    if runtime
+        .motion
+        .as_ref()
+        .is_none_or(|motion| motion.to != target)
+    {
+        let (easing, timing) = if target >= runtime.progress {
+            (door.opening_easing.clone(), door.opening_timing)
+        } else {
+            (door.closing_easing.clone(), door.closing_timing)
+        };
+        // a movement over only part of the way takes only part of the time.
+        // a negative or NaN duration makes the movement instant, instead of producing a NaN progress
+        let duration_secs = (timing.full_duration_secs(travel_distance)
+            * (target - runtime.progress).abs())
+        .max(0.0);
+
+        // the new motion starts with the velocity of the previous one, so reversing direction is smooth
+        runtime.motion = Some(DoorMotion::new(
+            runtime.progress,
+            target,
+            duration_secs,
+            easing,
+            runtime.velocity,
+        ));
+    }
+
+    let Some(motion) = runtime.motion.as_mut() else {
+        return false;
+    };
+
+    motion.elapsed_secs += delta_secs;
+
+    if motion.is_finished() {
+        // the progress is set directly so that the position corresponds exactly to the target
+        runtime.progress = target;
+        runtime.velocity = 0.0;
+        runtime.motion = None;
+        true
+    } else {
+        let previous_progress = runtime.progress;
+        runtime.progress = motion.sample();
+
+        if delta_secs > 0.0 {
+            runtime.velocity = (runtime.progress - previous_progress) / delta_secs;
+        }
+        false
+    }
+}
+
+pub fn slide_interpolate(closed: Vec3, open: Vec3, path: Option<&DoorPath>, t: f32) -> Vec3 {
+    match path {
+        Some(path) => path.sample(t),
+        None => closed.lerp(open, t),
+    }
+}
+
+/// rotates the door around its hinge. the transform should already have the translation the door would have without swinging
+pub fn swing_interpolate(swing: &SwingDoor, transform: &mut Transform, t: f32) {
+    let angle = swing.closed_angle + (swing.target_open_angle() - swing.closed_angle) * t;
+    let rotation = Quat::from_axis_angle(swing.axis.as_vec3(), angle);
+
+    let hinge = transform.translation + swing.base_rotation * (transform.scale * swing.pivot);
+
+    transform.translation = hinge + rotation * (transform.translation - hinge);
+    transform.rotation = rotation * swing.base_rotation;
+}
+
+/// computes where a door or leaf should be for the given progress, following the path and swinging if needed
+pub fn door_pose(
+    transform: &Transform,
+    closed: Vec3,
+    open: Vec3,
+    path: Option<&DoorPath>,
+    swing: Option<&SwingDoor>,
+    t: f32,
+) -> Transform {
+    let mut pose = *transform;
+    pose.translation = slide_interpolate(closed, open, path, t);
+
+    if let Some(swing) = swing {
+        swing_interpolate(swing, &mut pose, t);
+    }
+
+    pose
+}
+
+/// moves the entity to the pose, or only records it if something else is responsible for moving the entity
+pub fn write_pose(
+    transform: &mut Transform,
+    pose_target: Option<&mut DoorPoseTarget>,
+    pose: Transform,
+) {
+    match pose_target {
+        Some(pose_target) => pose_target.0 = pose,
+        None => *transform = pose,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::FRAC_PI_2;
+
+    use super::*;
+
+    const EPSILON: f32 = 1e-4;
+
+    #[test]
+    fn slide_travels_between_positions() {
+        let distance = travel_distance(Vec3::ZERO, Vec3::new(3.0, 4.0, 0.0), None, None);
+
+        assert!((distance - 5.0).abs() < EPSILON);
+    }
+
+    #[test]
+    fn swing_travels_its_far_edge() {
+        // the hinge on the origin of the door, like the default
+        let swing = SwingDoor::default();
+
+        let distance = travel_distance(Vec3::ZERO, Vec3::ZERO, None, Some(&swing));
+
+        assert!((distance - FRAC_PI_2).abs() < EPSILON);
+    }
+
+    #[test]
+    fn swing_scales_with_radius() {
+        let swing = SwingDoor {
+            radius: 2.0,
+            ..Default::default()
+        };
+
+        let distance = travel_distance(Vec3::ZERO, Vec3::X, None, Some(&swing));
+
+        assert!((distance - (1.0 + 2.0 * FRAC_PI_2)).abs() < EPSILON);
+    }
 }